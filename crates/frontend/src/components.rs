@@ -0,0 +1,325 @@
+// Typed page components rendered with dioxus-ssr. Text nodes are escaped by the renderer, but
+// dioxus-ssr 0.5 writes attribute values verbatim, so every dynamic attribute goes through `attr`.
+// The only raw HTML is the pre-rendered artifact/page body passed through `RawHtml`.
+
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::{Artifact, Facet};
+
+/// Per-page `<head>` metadata. `url`, `image` and `feed` are absolute and only set when the
+/// site URL is known (`--site-url`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageMeta {
+    pub title: String,
+    pub description: String,
+    pub url: Option<String>,
+    pub image: Option<String>,
+    pub feed: Option<String>,
+}
+
+/// Provenance facts shown in the artifact page's metadata panel.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArtifactDetails {
+    pub expected_sha256: String,
+    /// `None` when the file could not be read
+    pub computed_sha256: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub source_path: String,
+    pub commit: String,
+    pub workflow_run_id: String,
+    pub workflow_run_url: String,
+    pub workflow_run_attempt: u32,
+    /// Shell commands a reader can paste to re-check this artifact
+    pub verify_commands: Vec<String>,
+}
+
+/// An authored page in the top navigation; `current` marks the page being rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavLink {
+    pub label: String,
+    pub href: String,
+    pub current: bool,
+}
+
+/// One breadcrumb step; the last one (the current page) has no `href`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crumb {
+    pub label: String,
+    pub href: Option<String>,
+}
+
+/// Document contents (`<head>` metadata, site stylesheet, skip link, top navigation and `<main>`);
+/// dioxus has no `<html>` element, so `render_document` adds the doctype and root element.
+#[component]
+pub fn Layout(base: String, meta: PageMeta, nav: Vec<NavLink>, children: Element) -> Element {
+    let (title_attr, description) = (attr(&meta.title), attr(&meta.description));
+    let url = meta.url.as_deref().map(attr);
+    let image = meta.image.as_deref().map(attr);
+    let feed = meta.feed.as_deref().map(attr);
+    let css = attr(&format!("{}/assets/site.css", base));
+    rsx! {
+        head {
+            meta { charset: "utf-8" }
+            meta { name: "viewport", content: "width=device-width, initial-scale=1.0" }
+            title { "{meta.title}" }
+            meta { name: "description", content: "{description}" }
+            meta { "property": "og:type", content: "website" }
+            meta { "property": "og:title", content: "{title_attr}" }
+            meta { "property": "og:description", content: "{description}" }
+            meta { name: "twitter:card", content: "summary" }
+            meta { name: "twitter:title", content: "{title_attr}" }
+            meta { name: "twitter:description", content: "{description}" }
+            if let Some(url) = url {
+                meta { "property": "og:url", content: "{url}" }
+                link { rel: "canonical", href: "{url}" }
+            }
+            if let Some(image) = image {
+                meta { "property": "og:image", content: "{image}" }
+                meta { name: "twitter:image", content: "{image}" }
+            }
+            if let Some(feed) = feed {
+                link { rel: "alternate", r#type: "application/atom+xml", href: "{feed}" }
+            }
+            link { rel: "stylesheet", href: "{css}" }
+        }
+        body {
+            a { href: "#main", class: "skip-link", "Skip to content" }
+            div { class: "container",
+                TopNav { base: base.clone(), pages: nav }
+                main { id: "main", role: "main", {children} }
+            }
+        }
+    }
+}
+
+/// Built-in sections followed by the manifest's authored pages (in `nav_order`).
+#[component]
+pub fn TopNav(base: String, pages: Vec<NavLink>) -> Element {
+    let base = attr(&base);
+    let pages: Vec<(String, String, bool)> = pages.iter().map(|p| (attr(&p.href), p.label.clone(), p.current)).collect();
+    rsx! {
+        header { class: "topbar", role: "banner",
+            nav { "aria-label": "Global", class: "global-nav",
+                a { href: "{base}/index.html", "Home" }
+                for (href, label, current) in pages.into_iter() {
+                    if current {
+                        a { href: "{href}", "aria-current": "page", "{label}" }
+                    } else {
+                        a { href: "{href}", "{label}" }
+                    }
+                }
+                a { href: "{base}/artifacts/", "Artifacts" }
+                a { href: "{base}/badge/", "Badges" }
+                a { href: "{base}/artifacts/#search", "Search" }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn Breadcrumbs(trail: Vec<Crumb>) -> Element {
+    let last = trail.len().saturating_sub(1);
+    rsx! {
+        nav { "aria-label": "Breadcrumb",
+            for (i, c) in trail.iter().enumerate() {
+                if let Some(href) = c.href.as_deref().map(attr) {
+                    a { href: "{href}", "{c.label}" }
+                } else {
+                    span { "{c.label}" }
+                }
+                if i < last { " / " }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn Badge(verified: bool) -> Element {
+    if verified {
+        rsx! { span { class: "badge ok", "verified" } }
+    } else {
+        rsx! { span { class: "badge err", "digest mismatch" } }
+    }
+}
+
+#[component]
+pub fn KpiGrid(kpis: Vec<(String, String)>) -> Element {
+    if kpis.is_empty() {
+        return None;
+    }
+    rsx! {
+        div { class: "cards",
+            for (k, v) in kpis.iter() {
+                div { class: "card",
+                    h3 { "{k}" }
+                    p { strong { "{v}" } }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ArtifactCard(base: String, id: String, title: String, verified: bool, download_href: String) -> Element {
+    let view = attr(&format!("{}/a/{}/", base, id));
+    let download = attr(&download_href);
+    rsx! {
+        div { class: "card",
+            h3 { "{title}" }
+            p { class: "muted", "{id}" }
+            p { Badge { verified } }
+            p {
+                a { href: "{view}", "View" }
+                " \u{00b7} "
+                a { href: "{download}", "Download" }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn MetaPanel(id: String, title: String, render: String, media_type: String, verified: bool, download_href: String, details: ArtifactDetails) -> Element {
+    let download = attr(&download_href);
+    let run_href = attr(&details.workflow_run_url);
+    let computed = details.computed_sha256.clone().unwrap_or_else(|| "file missing".to_string());
+    let size = details.size_bytes.map(format_size).unwrap_or_else(|| "unknown".to_string());
+    rsx! {
+        aside { class: "right-meta", "aria-label": "Metadata",
+            div { class: "card",
+                h3 { "Metadata" }
+                dl { class: "meta",
+                    dt { "ID" }
+                    dd { "{id}" }
+                    dt { "Title" }
+                    dd { "{title}" }
+                    dt { "Render" }
+                    dd { code { "{render}" } }
+                    dt { "Media" }
+                    dd { "{media_type}" }
+                    dt { "Status" }
+                    dd { Badge { verified } }
+                    dt { "Expected SHA-256" }
+                    dd { code { class: "digest", "{details.expected_sha256}" } }
+                    dt { "Computed SHA-256" }
+                    dd { code { class: "digest", "{computed}" } }
+                    dt { "Size" }
+                    dd { "{size}" }
+                    dt { "Source" }
+                    dd { code { "{details.source_path}" } }
+                    dt { "Commit" }
+                    dd { code { "{details.commit}" } }
+                    dt { "Workflow run" }
+                    dd {
+                        a { href: "{run_href}", "{details.workflow_run_id}" }
+                        " (attempt {details.workflow_run_attempt})"
+                    }
+                    dt { "Download" }
+                    dd { a { href: "{download}", "file" } }
+                }
+            }
+            if !details.verify_commands.is_empty() {
+                div { class: "card",
+                    h3 { "Verify locally" }
+                    for cmd in details.verify_commands.iter() {
+                        pre { class: "copy", code { "{cmd}" } }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One row of an artifacts table; an owned copy of `Artifact` so it can be a component prop.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ArtifactRow {
+    pub id: String,
+    pub title: String,
+    pub render: String,
+    pub media_type: String,
+    pub verified: bool,
+}
+
+impl From<&Artifact<'_>> for ArtifactRow {
+    fn from(a: &Artifact<'_>) -> Self {
+        ArtifactRow { id: a.id.to_string(), title: a.title.to_string(), render: a.render.to_string(), media_type: a.media_type.to_string(), verified: a.verified }
+    }
+}
+
+#[component]
+pub(crate) fn ArtifactsTable(base: String, table_id: Option<String>, rows: Vec<ArtifactRow>) -> Element {
+    let base = attr(&base);
+    rsx! {
+        table { id: table_id, class: "table",
+            thead {
+                tr {
+                    th { scope: "col", "ID" }
+                    th { scope: "col", "Title" }
+                    th { scope: "col", "Render" }
+                    th { scope: "col", "Media" }
+                    th { scope: "col", "Verified" }
+                }
+            }
+            tbody {
+                for a in rows.iter() {
+                    tr { "data-id": attr(&a.id),
+                        th { scope: "row", a { href: "{base}/a/{attr(&a.id)}/", "{a.id}" } }
+                        td { "{a.title}" }
+                        td { "{a.render}" }
+                        td { "{a.media_type}" }
+                        td { Badge { verified: a.verified } }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Links to the listing pages (see `artifact_facets`) by field; `active` is the listing being shown.
+#[component]
+pub(crate) fn FacetNav(base: String, facets: Vec<Facet>, active: Option<Facet>) -> Element {
+    let base = attr(&base);
+    let groups = [("kind", "Kind"), ("status", "Verified"), ("media", "Media")].map(|(field, heading)| {
+        let links: Vec<(String, String, bool)> = facets.iter().filter(|f| f.field == field).map(|f| (attr(&f.path()), f.label.clone(), active.as_ref() == Some(f))).collect();
+        (heading, links)
+    });
+    rsx! {
+        nav { class: "facets", "aria-label": "Browse artifacts",
+            p { a { href: "{base}/artifacts/", "aria-current": active.is_none().then_some("page"), "All artifacts" } }
+            for (heading, links) in groups.into_iter() {
+                h3 { "{heading}" }
+                ul {
+                    for (href, label, current) in links.into_iter() {
+                        li { a { href: "{base}/{href}/", "aria-current": current.then_some("page"), "{label}" } }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {} ({} bytes)", value, UNITS[unit], bytes)
+}
+
+/// Pre-rendered, trusted HTML (artifact renderers, Proofdown output). Never pass manifest strings here.
+#[component]
+pub fn RawHtml(html: String) -> Element {
+    rsx! { div { dangerous_inner_html: "{html}" } }
+}
+
+/// Escape a value for use inside a double-quoted attribute.
+pub(crate) fn attr(s: &str) -> String {
+    s.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;").replace('>', "&gt;")
+}
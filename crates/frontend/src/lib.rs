@@ -1,5 +1,11 @@
+use dioxus::prelude::*;
 use std::collections::BTreeMap;
 
+mod components;
+
+use components::{attr, ArtifactRow, ArtifactsTable, FacetNav};
+pub use components::{ArtifactCard, ArtifactDetails, Badge, Breadcrumbs, Crumb, KpiGrid, Layout, MetaPanel, NavLink, PageMeta, RawHtml, TopNav};
+
 #[derive(Clone, Debug)]
 pub struct Artifact<'a> {
     pub id: &'a str,
@@ -10,95 +16,344 @@ pub struct Artifact<'a> {
     pub download_href: &'a str,
 }
 
-fn badge(verified: bool) -> &'static str {
-    if verified { "<span class=\"badge ok\">verified</span>" } else { "<span class=\"badge err\">digest mismatch</span>" }
+// `base` is the URL path prefix the site is served under ("" at the host root, otherwise
+// e.g. "/project/commit" without a trailing slash); every internal link is `{base}/...`.
+
+/// Complete HTML document for one page: `Layout` around an already rendered body. `nav` lists
+/// the authored pages for the top navigation.
+pub fn render_document(base: &str, meta: &PageMeta, nav: &[NavLink], body_html: String) -> String {
+    let page = dioxus::ssr::render_element(rsx! {
+        Layout { base: base.to_string(), meta: meta.clone(), nav: nav.to_vec(), RawHtml { html: body_html } }
+    });
+    format!("<!doctype html><html lang=\"en\">{}</html>", page)
 }
 
-fn esc(s: &str) -> String { s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;") }
+pub fn render_index<'a>(base: &str, title: &str, commit: &str, kpis: &BTreeMap<&str, String>, featured: &[Artifact<'a>]) -> String {
+    let kpis: Vec<(String, String)> = kpis.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+    dioxus::ssr::render_element(rsx! {
+        header { h1 { "{title} — {commit}" } }
+        KpiGrid { kpis }
+        h2 { "Artifacts" }
+        div { class: "cards",
+            for a in featured.iter() {
+                ArtifactCard {
+                    base: base.to_string(),
+                    id: a.id.to_string(),
+                    title: a.title.to_string(),
+                    verified: a.verified,
+                    download_href: a.download_href.to_string(),
+                }
+            }
+        }
+    })
+}
 
-pub fn render_index<'a>(title: &str, commit: &str, kpis: &BTreeMap<&str, String>, featured: &[Artifact<'a>]) -> String {
-    let mut out = String::new();
-    out.push_str(&format!("<header><h1>{} — {}</h1></header>", esc(title), esc(commit)));
-    if !kpis.is_empty() {
-        out.push_str("<div class=\"cards\">");
-        for (k, v) in kpis.iter() {
-            out.push_str(&format!("<div class=\"card\"><h3>{}</h3><p><strong>{}</strong></p></div>", esc(k), esc(v)));
+pub fn render_artifact<'a>(base: &str, a: &Artifact<'a>, details: &ArtifactDetails, body_html: &str) -> String {
+    let trail = vec![
+        Crumb { label: "Home".into(), href: Some(format!("{}/index.html", base)) },
+        Crumb { label: "Artifacts".into(), href: Some(format!("{}/artifacts/", base)) },
+        Crumb { label: a.title.to_string(), href: None },
+    ];
+    let all_href = attr(&format!("{}/artifacts/", base));
+    let download = attr(a.download_href);
+    dioxus::ssr::render_element(rsx! {
+        header { class: "page",
+            Breadcrumbs { trail }
+            h1 { "{a.title}" }
+            p { class: "muted", "{a.id}" }
         }
-        out.push_str("</div>");
+        // Content grid with left nav and right metadata
+        div { class: "content-grid",
+            aside { class: "left-nav", "aria-label": "Page navigation",
+                nav { ul { li { a { href: "{all_href}", "← All artifacts" } } } }
+            }
+            section {
+                p { Badge { verified: a.verified } }
+                article { dangerous_inner_html: "{body_html}" }
+                p { a { href: "{download}", "Download raw" } }
+            }
+            MetaPanel {
+                id: a.id.to_string(),
+                title: a.title.to_string(),
+                render: a.render.to_string(),
+                media_type: a.media_type.to_string(),
+                verified: a.verified,
+                download_href: a.download_href.to_string(),
+                details: details.clone(),
+            }
+        }
+    })
+}
+
+/// One pre-rendered listing under `/artifacts/{field}/{slug}/`: a render kind, a verification
+/// status or a media type. These are the no-JS way to narrow the artifacts index.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Facet {
+    /// "kind", "status" or "media"
+    pub field: &'static str,
+    /// Value as shown to readers, e.g. "summary:test" or "Digest mismatch"
+    pub label: String,
+    /// URL segment, e.g. "summary-test"
+    pub slug: String,
+}
+
+impl Facet {
+    /// Site-relative directory of the listing, e.g. "artifacts/kind/summary-test".
+    pub fn path(&self) -> String {
+        format!("artifacts/{}/{}", self.field, self.slug)
     }
-    out.push_str("<h2>Artifacts</h2><div class=\"cards\">");
-    for a in featured.iter() {
-        out.push_str(&format!(
-            "<div class=\"card\"><h3>{}</h3><p class=\"muted\">{}</p><p>{}</p><p><a href=\"/a/{}/\">View</a> \u{00b7} <a href=\"{}\">Download</a></p></div>",
-            esc(a.title), esc(a.id), badge(a.verified), esc(a.id), esc(a.download_href)
-        ));
+
+    fn matches(&self, a: &Artifact<'_>) -> bool {
+        match self.field {
+            "kind" => a.render == self.label,
+            "status" => status_slug(a.verified) == self.slug,
+            "media" => a.media_type == self.label,
+            _ => false,
+        }
     }
-    out.push_str("</div>");
-    out
 }
 
-pub fn render_artifact<'a>(a: &Artifact<'a>, body_html: &str) -> String {
+/// Lowercase ASCII alphanumerics, everything else collapsed to single dashes ("table:coverage" -> "table-coverage").
+pub fn slugify(s: &str) -> String {
     let mut out = String::new();
-    out.push_str(&format!("<header class=\"page\"><nav aria-label=\"Breadcrumb\"><a href=\"/index.html\">Home</a> / <a href=\"/artifacts/\">Artifacts</a> / <span>{}</span></nav><h1>{}</h1><p class=\"muted\">{}</p></header>", esc(a.title), esc(a.title), esc(a.id)));
-    // Content grid with left nav and right metadata
-    out.push_str("<div class=\"content-grid\">");
-    // Left navigation
-    out.push_str("<aside class=\"left-nav\" aria-label=\"Page navigation\">");
-    out.push_str("<nav><ul>");
-    out.push_str("<li><a href=\"/artifacts/\">← All artifacts</a></li>");
-    out.push_str("</ul></nav>");
-    out.push_str("</aside>");
-    // Main article
-    out.push_str("<section>");
-    out.push_str(&format!("<p>{}</p>", badge(a.verified)));
-    out.push_str(&format!("<article>{}</article>", body_html));
-    out.push_str(&format!("<p><a href=\"{}\">Download raw</a></p>", esc(a.download_href)));
-    out.push_str("</section>");
-    // Right metadata
-    out.push_str("<aside class=\"right-meta\" aria-label=\"Metadata\">");
-    out.push_str("<div class=\"card\"><h3>Metadata</h3>");
-    out.push_str("<dl class=\"meta\">");
-    out.push_str(&format!("<dt>ID</dt><dd>{}</dd>", esc(a.id)));
-    out.push_str(&format!("<dt>Title</dt><dd>{}</dd>", esc(a.title)));
-    out.push_str(&format!("<dt>Media</dt><dd>{}</dd>", esc(a.media_type)));
-    out.push_str(&format!("<dt>Status</dt><dd>{}</dd>", if a.verified { "verified" } else { "digest mismatch" }));
-    out.push_str(&format!("<dt>Download</dt><dd><a href=\"{}\">file</a></dd>", esc(a.download_href)));
-    out.push_str("</dl></div>");
-    out.push_str("</aside>");
-    // Close grid
-    out.push_str("</div>");
-    out
+    for ch in s.chars() {
+        if ch.is_ascii_alphanumeric() {
+            out.push(ch.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
 }
 
-pub fn render_artifacts_index<'a>(items: &[Artifact<'a>]) -> String {
-    let mut out = String::new();
-    out.push_str("<header class=\"page\"><h1>All Artifacts</h1></header>");
-    // Layout with left sidebar filters and main results
-    out.push_str("<div class=\"content-grid\">");
-    // Left filters sidebar with GET form and #search anchor
-    out.push_str("<aside class=\"left-nav\" aria-label=\"Filters\">");
-    out.push_str("<a id=\"search\"></a>");
-    out.push_str("<form class=\"filters\" method=\"get\" action=\"/artifacts/index.html\">");
-    out.push_str("<div class=\"row\"><label for=\"q\">Search</label><input id=\"q\" name=\"q\" type=\"search\" placeholder=\"id, title, kind\" autofocus></div>");
-    out.push_str("<div class=\"row\"><label for=\"kind\">Kind</label><select id=\"kind\" name=\"kind\"><option value=\"\">Any</option><option>summary:test</option><option>table:coverage</option><option>markdown</option><option>json</option><option>image</option></select></div>");
-    out.push_str("<div class=\"row\"><label for=\"verified\">Verified</label><select id=\"verified\" name=\"verified\"><option value=\"\">Any</option><option value=\"true\">Verified</option><option value=\"false\">Error</option></select></div>");
-    out.push_str("<div class=\"row\"><label for=\"media\">Media</label><select id=\"media\" name=\"media\"><option value=\"\">Any</option><option>application/json</option><option>text/markdown</option><option>image/*</option><option>text/*</option></select></div>");
-    out.push_str("<div class=\"row\"><label for=\"sort\">Sort by</label><select id=\"sort\" name=\"sort\"><option value=\"id\">ID</option><option value=\"title\">Title</option><option value=\"render\">Kind</option></select></div>");
-    out.push_str("<div class=\"row\"><span></span><button type=\"submit\">Apply</button></div>");
-    out.push_str("</form>");
-    out.push_str("</aside>");
-    // Main results table
-    out.push_str("<section>");
-    out.push_str("<table class=\"table\"><thead><tr><th scope=\"col\">ID</th><th scope=\"col\">Title</th><th scope=\"col\">Render</th><th scope=\"col\">Media</th><th scope=\"col\">Verified</th></tr></thead><tbody>");
-    for a in items.iter() {
-        out.push_str(&format!(
-            "<tr><th scope=\"row\"><a href=\"/a/{}/\">{}</a></th><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
-            esc(a.id), esc(a.id), esc(a.title), esc(a.render), esc(a.media_type), badge(a.verified)
-        ));
+fn status_slug(verified: bool) -> &'static str {
+    if verified { "verified" } else { "mismatch" }
+}
+
+/// All listings that have at least one artifact, sorted by field then value. Values whose slugs
+/// collide ("a.b", "a-b") or are empty get a numbered slug ("a-b-2", "value"), so every listing
+/// has its own directory.
+pub fn artifact_facets(items: &[Artifact<'_>]) -> Vec<Facet> {
+    use std::collections::BTreeSet;
+    let mut set = BTreeSet::new();
+    for a in items {
+        set.insert(Facet {
+            field: "status",
+            label: if a.verified { "Verified" } else { "Digest mismatch" }.to_string(),
+            slug: status_slug(a.verified).to_string(),
+        });
+    }
+    for (field, values) in [("kind", items.iter().map(|a| a.render).collect::<BTreeSet<_>>()), ("media", items.iter().map(|a| a.media_type).collect())] {
+        let mut taken = BTreeSet::new();
+        for value in values {
+            let base = Some(slugify(value)).filter(|s| !s.is_empty()).unwrap_or_else(|| "value".to_string());
+            let slug = (1..).map(|n| if n == 1 { base.clone() } else { format!("{}-{}", base, n) }).find(|s| !taken.contains(s)).unwrap();
+            taken.insert(slug.clone());
+            set.insert(Facet { field, label: value.to_string(), slug });
+        }
+    }
+    set.into_iter().collect()
+}
+
+pub fn render_artifacts_index<'a>(base: &str, items: &[Artifact<'a>]) -> String {
+    let facets = artifact_facets(items);
+    let options = |field: &str, value: fn(&Facet) -> String| -> Vec<(String, String)> {
+        facets.iter().filter(|f| f.field == field).map(|f| (attr(&value(f)), f.label.clone())).collect()
+    };
+    let kinds = options("kind", |f| f.label.clone());
+    let statuses = options("status", |f| (f.slug == "verified").to_string());
+    let media = options("media", |f| f.label.clone());
+    let rows: Vec<ArtifactRow> = items.iter().map(ArtifactRow::from).collect();
+    let href_base = attr(base);
+    dioxus::ssr::render_element(rsx! {
+        header { class: "page", h1 { "All Artifacts" } }
+        // Layout with left sidebar filters and main results
+        div { class: "content-grid",
+            // Left sidebar: static listing links (work without JS) and the #search anchor
+            aside { class: "left-nav", "aria-label": "Filters",
+                a { id: "search" }
+                // The form is hidden until assets/search.js takes over; it filters the table in place using
+                // the search index named in data-index, so nothing is ever submitted to the (static) host.
+                form { id: "artifact-filters", class: "filters", hidden: "true", "data-index": "{href_base}/search_index.json",
+                    div { class: "row",
+                        label { r#for: "q", "Search" }
+                        input { id: "q", name: "q", r#type: "search", placeholder: "id, title, kind, group, tag", autofocus: "true" }
+                    }
+                    div { class: "row",
+                        label { r#for: "kind", "Kind" }
+                        select { id: "kind", name: "kind",
+                            option { value: "", "Any" }
+                            for (value, label) in kinds.into_iter() {
+                                option { value: "{value}", "{label}" }
+                            }
+                        }
+                    }
+                    div { class: "row",
+                        label { r#for: "verified", "Verified" }
+                        select { id: "verified", name: "verified",
+                            option { value: "", "Any" }
+                            for (value, label) in statuses.into_iter() {
+                                option { value: "{value}", "{label}" }
+                            }
+                        }
+                    }
+                    div { class: "row",
+                        label { r#for: "media", "Media" }
+                        select { id: "media", name: "media",
+                            option { value: "", "Any" }
+                            for (value, label) in media.into_iter() {
+                                option { value: "{value}", "{label}" }
+                            }
+                        }
+                    }
+                    div { class: "row",
+                        label { r#for: "sort", "Sort by" }
+                        select { id: "sort", name: "sort",
+                            option { value: "id", "ID" }
+                            option { value: "title", "Title" }
+                            option { value: "render", "Kind" }
+                        }
+                    }
+                }
+                FacetNav { base: base.to_string(), facets: facets.clone() }
+                p { a { href: "{href_base}/search/", "Search artifact contents" } }
+            }
+            // Main results table; no right aside on the index page
+            section {
+                p { id: "artifact-count", class: "muted", "aria-live": "polite" }
+                ArtifactsTable { base: base.to_string(), table_id: "artifacts-table".to_string(), rows }
+            }
+        }
+        // External script (no inline JS) so a strict `script-src 'self'` CSP keeps working
+        script { src: "{href_base}/assets/search.js", defer: "true" }
+    })
+}
+
+/// Static listing page for one facet (see `artifact_facets`), with the same sidebar as the index.
+pub fn render_artifacts_listing<'a>(base: &str, items: &[Artifact<'a>], facet: &Facet) -> String {
+    let facets = artifact_facets(items);
+    let heading = match facet.field {
+        "kind" => "Kind",
+        "status" => "Verified",
+        _ => "Media",
+    };
+    let rows: Vec<ArtifactRow> = items.iter().filter(|a| facet.matches(a)).map(ArtifactRow::from).collect();
+    let (shown, total) = (rows.len(), items.len());
+    let trail = vec![
+        Crumb { label: "Home".into(), href: Some(format!("{}/index.html", base)) },
+        Crumb { label: "Artifacts".into(), href: Some(format!("{}/artifacts/", base)) },
+        Crumb { label: facet.label.clone(), href: None },
+    ];
+    dioxus::ssr::render_element(rsx! {
+        header { class: "page",
+            Breadcrumbs { trail }
+            h1 { "{heading}: {facet.label}" }
+            p { class: "muted", "{shown} of {total} artifacts" }
+        }
+        div { class: "content-grid",
+            aside { class: "left-nav", "aria-label": "Filters",
+                FacetNav { base: base.to_string(), facets, active: facet.clone() }
+            }
+            section {
+                ArtifactsTable { base: base.to_string(), rows }
+            }
+        }
+    })
+}
+
+/// Full-text results page at `/search/?q=...`; results are filled in by assets/fulltext.js
+/// from the sharded index under `search/`.
+pub fn render_search_page(base: &str) -> String {
+    let trail = vec![
+        Crumb { label: "Home".into(), href: Some(format!("{}/index.html", base)) },
+        Crumb { label: "Search".into(), href: None },
+    ];
+    let href_base = attr(base);
+    dioxus::ssr::render_element(rsx! {
+        header { class: "page",
+            Breadcrumbs { trail }
+            h1 { "Search artifact contents" }
+        }
+        form { class: "filters", method: "get", action: "{href_base}/search/", role: "search",
+            div { class: "row",
+                label { r#for: "q", "Search" }
+                input { id: "q", name: "q", r#type: "search", placeholder: "words from reports, test names, file paths" }
+            }
+            div { class: "row",
+                span {}
+                button { r#type: "submit", "Search" }
+            }
+        }
+        noscript {
+            p { class: "muted",
+                "Full-text search needs JavaScript. Browse "
+                a { href: "{href_base}/artifacts/", "all artifacts" }
+                " instead."
+            }
+        }
+        p { id: "search-status", class: "muted", "aria-live": "polite" }
+        ol { id: "search-results", class: "search-results", "data-base": "{href_base}" }
+        script { src: "{href_base}/assets/fulltext.js", defer: "true" }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components_escape_manifest_strings() {
+        let a = Artifact {
+            id: "x",
+            title: "<script>alert(1)</script>",
+            render: "markdown",
+            media_type: "text/markdown",
+            verified: false,
+            download_href: "/assets/x/\"onmouseover=\"x.md",
+        };
+        let html = render_artifact("", &a, &ArtifactDetails::default(), "<p>trusted body</p>");
+        assert!(html.contains("<h1>&lt;script&gt;alert(1)&lt;/script&gt;</h1>"));
+        assert!(!html.contains("\"onmouseover=\""));
+        assert!(html.contains("<article><p>trusted body</p></article>"));
+
+        let meta = PageMeta { title: "A & B".into(), ..Default::default() };
+        let nav = [NavLink { label: "Release <checklist>".into(), href: "/base/p/release/".into(), current: true }];
+        let doc = render_document("/base", &meta, &nav, html);
+        assert!(doc.starts_with("<!doctype html><html lang=\"en\"><head>"));
+        assert!(doc.contains("<a href=\"/base/p/release/\" aria-current=\"page\">Release &lt;checklist&gt;</a>"));
+        assert!(doc.contains("<title>A &amp; B</title>"));
+        assert!(doc.contains("<link rel=\"stylesheet\" href=\"/base/assets/site.css\"/>"));
+    }
+
+    #[test]
+    fn facet_slugs_are_unique_and_never_empty() {
+        let artifact = |render, media_type| Artifact { id: "x", title: "X", render, media_type, verified: true, download_href: "" };
+        let items = [artifact("a.b", "text/plain"), artifact("a-b", "text/plain"), artifact("a_b", "::"), artifact("markdown", "text/plain")];
+        let facets = artifact_facets(&items);
+        let got: Vec<(&str, &str, &str)> = facets.iter().map(|f| (f.field, f.label.as_str(), f.slug.as_str())).collect();
+        assert_eq!(
+            got,
+            [
+                ("kind", "a-b", "a-b"),
+                ("kind", "a.b", "a-b-2"),
+                ("kind", "a_b", "a-b-3"),
+                ("kind", "markdown", "markdown"),
+                ("media", "::", "value"),
+                ("media", "text/plain", "text-plain"),
+                ("status", "Verified", "verified"),
+            ]
+        );
+        let listing = render_artifacts_listing("", &items, &facets[1]);
+        assert!(listing.contains("1 of 4 artifacts"), "{}", listing);
+    }
+
+    #[test]
+    fn listing_pages_escape_attribute_values() {
+        let items = [Artifact { id: "x\"onclick=\"y", title: "X", render: "a\"b", media_type: "text/plain", verified: true, download_href: "" }];
+        let base = "/p\"q";
+        let pages = [render_artifacts_index(base, &items), render_artifacts_listing(base, &items, &artifact_facets(&items)[0]), render_search_page(base)];
+        for html in pages {
+            assert!(!html.contains("p\"q") && !html.contains("x\"onclick"), "{}", html);
+        }
     }
-    out.push_str("</tbody></table>");
-    out.push_str("</section>");
-    // No right aside on index page
-    out.push_str("</div>");
-    out
 }
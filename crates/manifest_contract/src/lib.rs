@@ -15,6 +15,9 @@ pub struct Manifest {
     pub workflow_run: WorkflowRun,
     pub front_page: FrontPage,
     pub artifacts: Vec<Artifact>,
+    /// Additional Proofdown pages, published under `/p/{id}/` and linked from the top navigation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<Page>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -28,6 +31,23 @@ pub struct WorkflowRun {
 pub struct FrontPage {
     pub title: String,
     pub markup: String,
+    /// Id of a `proofdown:ast` artifact: the front page pre-parsed, for builds without the Proofdown parser
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ast: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Page {
+    pub id: String,
+    pub title: String,
+    /// Repo-relative path of the Proofdown source
+    pub markup: String,
+    /// Position in the top navigation (ascending; ties are ordered by id)
+    #[serde(default)]
+    pub nav_order: i32,
+    /// Id of a `proofdown:ast` artifact: the page pre-parsed, for builds without the Proofdown parser
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ast: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -38,6 +58,12 @@ pub struct Artifact {
     pub media_type: String,
     pub render: String,
     pub sha256: String,
+    /// Optional grouping label used for browsing and search (e.g. "tests", "coverage")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Optional free-form tags used for search
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 /// Load manifest JSON as typed struct and raw JSON value
@@ -112,6 +138,7 @@ pub fn ed25519_verify(canonical_bytes: &[u8], signature_b64: &str, pubkey_b64_or
 /// - Unique artifact ids
 /// - Path normalization (reject `..`, absolute, or leading slash)
 /// - Allowed render values (defensive check; schema already enumerates)
+/// - Unique page ids that do not collide with `proofdown` artifacts (both are served at `/p/{id}/`)
 pub fn validate_semantics(m: &Manifest, root: impl AsRef<Path>) -> Result<()> {
     // Unique IDs
     let mut seen = HashSet::new();
@@ -137,16 +164,43 @@ pub fn validate_semantics(m: &Manifest, root: impl AsRef<Path>) -> Result<()> {
                 return Err(anyhow!("artifact path escapes root: {}", a.path));
             }
         }
-        // Defensive render set (kept in sync with schema)
-        match a.render.as_str() {
-            "markdown" | "json" | "table:coverage" | "summary:test" | "image" | "repo:file" | "repo:bundle" | "repo:symbols" => {}
-            other => return Err(anyhow!("unknown render: {} for id {}", other, a.id)),
-        }
+        // Render hint and media type against the hint table (kept in sync with schema)
+        render_hints::check_artifact(&a.id, &a.render, &a.media_type).map_err(|e| anyhow!(e))?;
         // sha256 format (defensive)
         if a.sha256.len() != 64 || !a.sha256.chars().all(|c| c.is_ascii_hexdigit() && c.is_ascii_lowercase() || c.is_ascii_digit()) {
             return Err(anyhow!("invalid sha256 for id {}: {}", a.id, a.sha256));
         }
     }
+
+    if let Some(id) = &m.front_page.ast {
+        check_ast_ref(m, "front_page.ast", id)?;
+    }
+
+    // Pages
+    let mut page_ids = HashSet::new();
+    for p in &m.pages {
+        if !page_ids.insert(&p.id) {
+            return Err(anyhow!("duplicate page id: {}", p.id));
+        }
+        if m.artifacts.iter().any(|a| a.id == p.id && a.render == "proofdown") {
+            return Err(anyhow!("page id {} collides with a proofdown artifact (both publish /p/{}/)", p.id, p.id));
+        }
+        if p.markup.starts_with('/') || p.markup.contains("..") {
+            return Err(anyhow!("page markup must be a repo-relative path without '..': {}", p.markup));
+        }
+        if let Some(id) = &p.ast {
+            check_ast_ref(m, &format!("page {} ast", p.id), id)?;
+        }
+    }
     Ok(())
 }
 
+/// `what` (e.g. "front_page.ast") must name a `proofdown:ast` artifact.
+fn check_ast_ref(m: &Manifest, what: &str, id: &str) -> Result<()> {
+    match m.artifacts.iter().find(|a| a.id == id) {
+        Some(a) if a.render == "proofdown:ast" => Ok(()),
+        Some(a) => Err(anyhow!("{} {} has render {}, expected proofdown:ast", what, id, a.render)),
+        None => Err(anyhow!("{} references unknown artifact: {}", what, id)),
+    }
+}
+
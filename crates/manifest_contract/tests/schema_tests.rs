@@ -15,6 +15,18 @@ fn schema_success_on_example_manifest() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn schema_accepts_media_type_parameters() -> Result<()> {
+    let crate_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let schema_txt = std::fs::read_to_string(crate_dir.join("../../schemas/manifest.schema.json"))?;
+    let mut val: Value = serde_json::from_str(&std::fs::read_to_string(crate_dir.join("../../examples/minimal/.provenance/manifest.json"))?)?;
+    val["artifacts"][0]["media_type"] = "application/json; charset=utf-8".into();
+    manifest_contract::validate_schema(&val, &schema_txt)?;
+    val["artifacts"][0]["media_type"] = "application/json charset".into();
+    assert!(manifest_contract::validate_schema(&val, &schema_txt).is_err());
+    Ok(())
+}
+
 #[test]
 fn schema_failure_on_missing_artifacts() {
     let bad = serde_json::json!({
@@ -31,3 +43,18 @@ fn schema_failure_on_missing_artifacts() {
     let msg = format!("{}", err);
     assert!(msg.contains("schema validation failed"));
 }
+
+#[test]
+fn schema_render_enum_matches_render_hints() -> Result<()> {
+    let schema_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../schemas/manifest.schema.json");
+    let schema: Value = serde_json::from_str(&std::fs::read_to_string(schema_path)?)?;
+    let hints: Vec<&str> = schema["properties"]["artifacts"]["items"]["properties"]["render"]["enum"]
+        .as_array()
+        .expect("render enum")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    let registered: Vec<&str> = render_hints::HINTS.iter().map(|h| h.name).collect();
+    assert_eq!(hints, registered, "add new render hints to both the schema and render_hints::HINTS");
+    Ok(())
+}
@@ -0,0 +1,463 @@
+// Offline evidence bundles for compliance retention: a deterministic tar (sorted entries, zeroed
+// mtimes/uids) holding the manifest, its signature, the schema, every artifact, the rendered site
+// and a `verifier.json` inventory, plus the matching `verify-bundle` check that needs nothing but
+// the archive and a pinned public key.
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use manifest_contract as mc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::ssg::{run_with_args, Args};
+
+const BUNDLE_FORMAT: u32 = 1;
+const VERIFIER_ENTRY: &str = "verifier.json";
+const SCHEMA_ENTRY: &str = "schema.json";
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct BundleArgs {
+    #[command(flatten)]
+    pub build: Args,
+
+    /// Output path of the evidence archive
+    #[arg(long, default_value = "evidence.tar")]
+    pub file: PathBuf,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct VerifyBundleArgs {
+    /// Evidence archive produced by `bundle`
+    #[arg(long, default_value = "evidence.tar")]
+    pub file: PathBuf,
+
+    /// Expected public key (Base64 or hex); must match the fingerprint recorded in the bundle
+    #[arg(long)]
+    pub pubkey: Option<String>,
+
+    /// Without --pubkey, accept the key stored in the bundle. This only proves the archive is
+    /// internally consistent, not who signed it.
+    #[arg(long, default_value_t = false)]
+    pub allow_embedded_key: bool,
+}
+
+/// Verifier metadata stored at `verifier.json` inside the archive.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifierMeta {
+    pub format: u32,
+    /// Archive entry of the manifest (under `root/`)
+    pub manifest: String,
+    /// Archive entry of the detached Ed25519 signature
+    pub signature: String,
+    /// SHA-256 over the canonicalized manifest
+    pub manifest_sha256: String,
+    /// Public key the manifest was verified with (Base64) and its SHA-256 fingerprint
+    pub pubkey: String,
+    pub pubkey_fingerprint: String,
+    /// SHA-256 of every other archive entry, keyed by entry path
+    pub files: BTreeMap<String, String>,
+}
+
+/// The site is rendered into a fresh temporary directory (not `--out`), so the archive holds
+/// exactly what this build produced.
+pub fn bundle(opts: BundleArgs) -> Result<()> {
+    let site = fresh_dir()?;
+    let result = write_bundle(&opts, &site);
+    let _ = fs::remove_dir_all(&site);
+    result
+}
+
+fn write_bundle(opts: &BundleArgs, site: &Path) -> Result<()> {
+    let mut args = opts.build.clone();
+    args.verify_manifest = true;
+    args.out = site.to_path_buf();
+    let pubkey = args.pubkey.clone().ok_or_else(|| anyhow!("--pubkey is required to bundle evidence"))?;
+    run_with_args(args.clone())?;
+
+    let manifest_path = args.root.join(&args.manifest);
+    let sig_path = manifest_path.with_extension("json.sig");
+    let (manifest, _) = mc::load_manifest(&manifest_path)?;
+    let schema_path = args.schema_path.clone().unwrap_or_else(|| PathBuf::from("schemas/manifest.schema.json"));
+
+    // Project inputs are stored under root/ at their repo-relative paths.
+    let mut entries: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    let manifest_entry = root_entry(&args.manifest)?;
+    let sig_entry = format!("{}.sig", manifest_entry);
+    entries.insert(manifest_entry.clone(), read(&manifest_path)?);
+    entries.insert(sig_entry.clone(), read(&sig_path)?);
+    for markup in std::iter::once(&manifest.front_page.markup).chain(manifest.pages.iter().map(|p| &p.markup)) {
+        entries.insert(root_entry(Path::new(markup))?, read(&args.root.join(markup))?);
+    }
+    for a in &manifest.artifacts {
+        let data = read(&args.root.join(&a.path))?;
+        // A bundle is retained as proof; refuse to archive evidence that already fails verification.
+        if sha256_hex(&data) != a.sha256 {
+            return Err(anyhow!("artifact digest mismatch for id {}; refusing to bundle", a.id));
+        }
+        entries.insert(root_entry(Path::new(&a.path))?, data);
+    }
+    entries.insert(SCHEMA_ENTRY.to_string(), read(&schema_path)?);
+    for e in WalkDir::new(&args.out).sort_by_file_name() {
+        let e = e?;
+        if e.file_type().is_file() {
+            let rel = e.path().strip_prefix(&args.out)?;
+            entries.insert(format!("site/{}", slash_path(rel)?), read(e.path())?);
+        }
+    }
+
+    let (_, manifest_json) = mc::load_manifest(&manifest_path)?;
+    let pk_bytes = decode_pubkey(&pubkey)?;
+    let meta = VerifierMeta {
+        format: BUNDLE_FORMAT,
+        manifest: manifest_entry,
+        signature: sig_entry,
+        manifest_sha256: sha256_hex(&mc::canonicalize(&manifest_json)),
+        pubkey: B64.encode(&pk_bytes),
+        pubkey_fingerprint: sha256_hex(&pk_bytes),
+        files: entries.iter().map(|(k, v)| (k.clone(), sha256_hex(v))).collect(),
+    };
+    entries.insert(VERIFIER_ENTRY.to_string(), (serde_json::to_string_pretty(&meta)? + "\n").into_bytes());
+
+    let tar = write_tar(&entries)?;
+    if let Some(parent) = opts.file.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    fs::write(&opts.file, tar).with_context(|| format!("write {}", opts.file.display()))?;
+    println!("Evidence bundle written to {} (key fingerprint {})", opts.file.display(), meta.pubkey_fingerprint);
+    Ok(())
+}
+
+pub fn verify_bundle(opts: VerifyBundleArgs) -> Result<()> {
+    let bytes = read(&opts.file)?;
+    let meta = verify_bundle_bytes(&bytes, opts.pubkey.as_deref())?;
+    if opts.pubkey.is_none() {
+        eprintln!(
+            "WARNING: no --pubkey given; the signature was checked against the key stored in the bundle itself (fingerprint {}). \
+             Anyone who can rewrite the archive can replace that key.",
+            meta.pubkey_fingerprint
+        );
+        if !opts.allow_embedded_key {
+            return Err(anyhow!("refusing to trust the embedded key; pass --pubkey <key> (or --allow-embedded-key to accept it)"));
+        }
+    }
+    println!(
+        "Bundle OK: manifest {} signed by key {}, {} files verified",
+        meta.manifest_sha256,
+        meta.pubkey_fingerprint,
+        meta.files.len()
+    );
+    Ok(())
+}
+
+/// Re-verify a bundle from its bytes alone: inventory digests, schema, manifest signature and
+/// every artifact digest declared in the manifest. Without `pinned_pubkey` the signature is only
+/// checked against the embedded key; callers must say so (see `verify_bundle`).
+pub fn verify_bundle_bytes(tar: &[u8], pinned_pubkey: Option<&str>) -> Result<VerifierMeta> {
+    let entries = read_tar(tar)?;
+    let meta_bytes = entries.get(VERIFIER_ENTRY).ok_or_else(|| anyhow!("bundle has no {}", VERIFIER_ENTRY))?;
+    let meta: VerifierMeta = serde_json::from_slice(meta_bytes).context("parse verifier.json")?;
+    if meta.format != BUNDLE_FORMAT {
+        return Err(anyhow!("unsupported bundle format {}", meta.format));
+    }
+
+    // Inventory: every entry accounted for, nothing added or altered
+    for (name, data) in &entries {
+        if name == VERIFIER_ENTRY { continue; }
+        let want = meta.files.get(name).ok_or_else(|| anyhow!("unexpected bundle entry: {}", name))?;
+        if &sha256_hex(data) != want {
+            return Err(anyhow!("digest mismatch for bundle entry: {}", name));
+        }
+    }
+    for name in meta.files.keys() {
+        if !entries.contains_key(name) {
+            return Err(anyhow!("bundle entry missing: {}", name));
+        }
+    }
+
+    // Public key: embedded key must match its fingerprint and, if given, the pinned key
+    let pk_bytes = B64.decode(meta.pubkey.trim()).context("base64-decode bundled public key")?;
+    if sha256_hex(&pk_bytes) != meta.pubkey_fingerprint {
+        return Err(anyhow!("bundled public key does not match its fingerprint"));
+    }
+    if let Some(pinned) = pinned_pubkey {
+        if decode_pubkey(pinned)? != pk_bytes {
+            return Err(anyhow!("bundle was signed with key {}, not the expected key", meta.pubkey_fingerprint));
+        }
+    }
+
+    // Manifest: schema, canonical digest and signature
+    let manifest_bytes = entry(&entries, &meta.manifest)?;
+    let manifest_json: serde_json::Value = serde_json::from_slice(manifest_bytes).context("parse bundled manifest")?;
+    let manifest: mc::Manifest = serde_json::from_value(manifest_json.clone()).context("deserialize bundled manifest")?;
+    let schema_text = std::str::from_utf8(entry(&entries, SCHEMA_ENTRY)?).context("schema is not UTF-8")?;
+    mc::validate_schema(&manifest_json, schema_text)?;
+    let canonical = mc::canonicalize(&manifest_json);
+    if sha256_hex(&canonical) != meta.manifest_sha256 {
+        return Err(anyhow!("manifest digest does not match verifier metadata"));
+    }
+    let sig_b64 = std::str::from_utf8(entry(&entries, &meta.signature)?).context("signature is not UTF-8")?;
+    if !mc::ed25519_verify(&canonical, sig_b64, &meta.pubkey).context("verify Ed25519 signature")? {
+        return Err(anyhow!("manifest signature verification failed"));
+    }
+
+    // Artifacts: bytes in the archive must match the signed digests
+    for a in &manifest.artifacts {
+        let name = root_entry(Path::new(&a.path))?;
+        let data = entry(&entries, &name)?;
+        if sha256_hex(data) != a.sha256 {
+            return Err(anyhow!("artifact digest mismatch for id {}", a.id));
+        }
+    }
+    Ok(meta)
+}
+
+fn entry<'a>(entries: &'a BTreeMap<String, Vec<u8>>, name: &str) -> Result<&'a [u8]> {
+    entries.get(name).map(|v| v.as_slice()).ok_or_else(|| anyhow!("bundle entry missing: {}", name))
+}
+
+fn fresh_dir() -> Result<PathBuf> {
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_nanos();
+    let dir = std::env::temp_dir().join(format!("provenance-bundle-{}-{}", std::process::id(), nanos));
+    fs::create_dir(&dir).with_context(|| format!("create {}", dir.display()))?;
+    Ok(dir)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| format!("read {}", path.display()))
+}
+
+fn root_entry(rel: &Path) -> Result<String> {
+    Ok(format!("root/{}", slash_path(rel)?))
+}
+
+fn slash_path(rel: &Path) -> Result<String> {
+    let mut segs = Vec::new();
+    for c in rel.components() {
+        match c {
+            std::path::Component::Normal(s) => segs.push(s.to_str().ok_or_else(|| anyhow!("non UTF-8 path: {}", rel.display()))?),
+            std::path::Component::CurDir => {}
+            _ => return Err(anyhow!("bundle paths must be relative and normalized: {}", rel.display())),
+        }
+    }
+    Ok(segs.join("/"))
+}
+
+fn decode_pubkey(s: &str) -> Result<Vec<u8>> {
+    let s = s.trim();
+    let bytes = match B64.decode(s) {
+        Ok(b) => b,
+        Err(_) => hex::decode(s).context("hex-decode public key")?,
+    };
+    if bytes.len() != 32 {
+        return Err(anyhow!("public key must be 32 bytes"));
+    }
+    Ok(bytes)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+// Minimal ustar writer/reader: regular files only, mode 0644, uid/gid 0, mtime 0.
+
+fn write_tar(entries: &BTreeMap<String, Vec<u8>>) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    for (name, data) in entries {
+        let mut h = [0u8; 512];
+        let (prefix, base) = split_ustar_name(name)?;
+        h[..base.len()].copy_from_slice(base.as_bytes());
+        put_octal(&mut h[100..108], 0o644)?;
+        put_octal(&mut h[108..116], 0)?;
+        put_octal(&mut h[116..124], 0)?;
+        put_octal(&mut h[124..136], data.len() as u64).with_context(|| format!("bundle entry too large: {}", name))?;
+        put_octal(&mut h[136..148], 0)?;
+        h[156] = b'0';
+        h[257..263].copy_from_slice(b"ustar\0");
+        h[263..265].copy_from_slice(b"00");
+        h[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+        h[148..156].copy_from_slice(b"        ");
+        let sum: u32 = h.iter().map(|b| *b as u32).sum();
+        let chk = format!("{:06o}\0 ", sum);
+        h[148..156].copy_from_slice(chk.as_bytes());
+        out.extend_from_slice(&h);
+        out.extend_from_slice(data);
+        out.resize(out.len().div_ceil(512) * 512, 0);
+    }
+    out.resize(out.len() + 1024, 0);
+    Ok(out)
+}
+
+pub(crate) fn read_tar(tar: &[u8]) -> Result<BTreeMap<String, Vec<u8>>> {
+    let mut entries = BTreeMap::new();
+    let mut off = 0usize;
+    while off + 512 <= tar.len() {
+        let h = &tar[off..off + 512];
+        if h.iter().all(|b| *b == 0) { break; }
+        let size = parse_octal(&h[124..136])? as usize;
+        let name = cstr(&h[0..100]);
+        let prefix = cstr(&h[345..500]);
+        let full = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+        let start = off + 512;
+        let end = start.checked_add(size).filter(|e| *e <= tar.len()).ok_or_else(|| anyhow!("truncated bundle entry: {}", full))?;
+        let is_file = h[156] == b'0' || h[156] == 0;
+        if is_file && entries.insert(full.clone(), tar[start..end].to_vec()).is_some() {
+            return Err(anyhow!("duplicate bundle entry: {}", full));
+        }
+        off = start + size.div_ceil(512) * 512;
+    }
+    Ok(entries)
+}
+
+fn split_ustar_name(name: &str) -> Result<(&str, &str)> {
+    if name.len() <= 100 {
+        return Ok(("", name));
+    }
+    // Split at a '/' so that prefix fits 155 bytes and the remainder fits 100 bytes
+    for (i, _) in name.match_indices('/') {
+        if i <= 155 && name.len() - i - 1 <= 100 {
+            return Ok((&name[..i], &name[i + 1..]));
+        }
+    }
+    Err(anyhow!("path too long for bundle: {}", name))
+}
+
+fn put_octal(field: &mut [u8], v: u64) -> Result<()> {
+    let s = format!("{:0width$o}\0", v, width = field.len() - 1);
+    if s.len() != field.len() {
+        return Err(anyhow!("{} does not fit a {}-byte tar header field", v, field.len()));
+    }
+    field.copy_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn parse_octal(field: &[u8]) -> Result<u64> {
+    let s = cstr(field);
+    u64::from_str_radix(s.trim(), 8).map_err(|_| anyhow!("invalid octal field in bundle header"))
+}
+
+fn cstr(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssg::test_support::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn unique_path(prefix: &str) -> PathBuf {
+        let mut p = std::env::temp_dir();
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        p.push(format!("{}-{}", prefix, nanos));
+        p
+    }
+
+    /// Copy of examples/minimal with fresh digests, signed with a fixed test key.
+    fn signed_fixture() -> (PathBuf, String) {
+        let example = example();
+        let root = unique_path("prov-bundle-root");
+        for e in WalkDir::new(&example) {
+            let e = e.unwrap();
+            let dest = root.join(e.path().strip_prefix(&example).unwrap());
+            if e.file_type().is_dir() { fs::create_dir_all(&dest).unwrap(); } else { fs::copy(e.path(), &dest).unwrap(); }
+        }
+        let pubkey = sign(&root);
+        (root, pubkey)
+    }
+
+    /// Refresh the artifact digests of a fixture and sign its manifest again.
+    fn sign(root: &Path) -> String {
+        use ed25519_dalek::{Signer, SigningKey};
+        let manifest_path = root.join(".provenance/manifest.json");
+        let (_, mut val) = mc::load_manifest(&manifest_path).unwrap();
+        for a in val["artifacts"].as_array_mut().unwrap() {
+            let data = fs::read(root.join(a["path"].as_str().unwrap())).unwrap();
+            a["sha256"] = serde_json::Value::String(sha256_hex(&data));
+        }
+        fs::write(&manifest_path, serde_json::to_string_pretty(&val).unwrap()).unwrap();
+        let sk = SigningKey::from_bytes(&[7u8; 32]);
+        let sig = sk.sign(&mc::canonicalize(&val));
+        fs::write(manifest_path.with_extension("json.sig"), B64.encode(sig.to_bytes())).unwrap();
+        B64.encode(sk.verifying_key().to_bytes())
+    }
+
+    fn bundle_args(root: &Path, pubkey: &str, file: PathBuf) -> BundleArgs {
+        BundleArgs {
+            build: Args { verify_manifest: true, pubkey: Some(pubkey.to_string()), ..test_args(root.to_path_buf(), unique_path("prov-bundle-site")) },
+            file,
+        }
+    }
+
+    #[test]
+    fn tar_roundtrip_keeps_long_names() {
+        let mut m = BTreeMap::new();
+        m.insert("a.txt".to_string(), b"hello".to_vec());
+        m.insert(format!("{}/{}", "d".repeat(120), "f".repeat(90)), vec![7u8; 1000]);
+        let tar = write_tar(&m).unwrap();
+        assert_eq!(tar.len() % 512, 0);
+        assert_eq!(read_tar(&tar).unwrap(), m);
+    }
+
+    #[test]
+    fn octal_fields_reject_values_that_do_not_fit() {
+        let mut field = [0u8; 12];
+        put_octal(&mut field, 0o77777777777).unwrap();
+        assert_eq!(&field, b"77777777777\0");
+        assert!(put_octal(&mut field, 0o100000000000).is_err());
+    }
+
+    #[test]
+    fn bundle_is_deterministic_and_tamper_evident() {
+        let (root, pubkey) = signed_fixture();
+        let f1 = unique_path("prov-bundle-1.tar");
+        let f2 = unique_path("prov-bundle-2.tar");
+        bundle(bundle_args(&root, &pubkey, f1.clone())).expect("bundle succeeds");
+        bundle(bundle_args(&root, &pubkey, f2.clone())).expect("bundle succeeds");
+        let b1 = fs::read(&f1).unwrap();
+        assert_eq!(b1, fs::read(&f2).unwrap(), "bundle bytes differ across runs");
+        verify_bundle_bytes(&b1, Some(&pubkey)).expect("fresh bundle verifies");
+        assert!(verify_bundle_bytes(&b1, Some(&B64.encode([1u8; 32]))).is_err(), "pinned key mismatch is rejected");
+
+        let mut entries = read_tar(&b1).unwrap();
+        entries.insert("root/ci/tests/summary.json".into(), b"{}".to_vec());
+        let tampered = write_tar(&entries).unwrap();
+        assert!(verify_bundle_bytes(&tampered, None).is_err());
+
+        let verify = |pubkey: Option<&str>, allow_embedded_key| verify_bundle(VerifyBundleArgs { file: f1.clone(), pubkey: pubkey.map(str::to_string), allow_embedded_key });
+        verify(Some(&pubkey), false).expect("pinned key verifies");
+        assert!(verify(None, false).is_err(), "the embedded key is not trusted by default");
+        verify(None, true).expect("embedded key accepted on request");
+    }
+
+    #[test]
+    fn bundle_packs_only_this_build() {
+        let (root, pubkey) = signed_fixture();
+        let args = bundle_args(&root, &pubkey, unique_path("prov-bundle.tar"));
+        fs::create_dir_all(&args.build.out).unwrap();
+        fs::write(args.build.out.join("stale.html"), "left over").unwrap();
+        bundle(args.clone()).expect("bundle succeeds");
+        let entries = read_tar(&fs::read(&args.file).unwrap()).unwrap();
+        assert!(entries.contains_key("site/index.html"));
+        assert!(!entries.contains_key("site/stale.html"));
+    }
+
+    #[test]
+    fn bundle_archives_page_sources() {
+        let (root, _) = signed_fixture();
+        edit_manifest(&root, |manifest| {
+            manifest["pages"] = serde_json::json!([{"id": "spec", "title": "Spec", "markup": "ci/pages/spec.pml"}]);
+        });
+        fs::create_dir_all(root.join("ci/pages")).unwrap();
+        fs::write(root.join("ci/pages/spec.pml"), "# Spec\n").unwrap();
+        let pubkey = sign(&root);
+        let args = bundle_args(&root, &pubkey, unique_path("prov-bundle.tar"));
+        bundle(args.clone()).expect("bundle succeeds");
+        let entries = read_tar(&fs::read(&args.file).unwrap()).unwrap();
+        assert_eq!(entries.get("root/ci/pages/spec.pml").map(Vec::as_slice), Some(&b"# Spec\n"[..]));
+    }
+}
@@ -0,0 +1,82 @@
+// Evidence coverage: every artifact should be shown or linked by some Proofdown page. Gaps are
+// reported on stderr, and fail the build with `--strict-evidence-coverage`. Without any page
+// rendered from Proofdown there is nothing to check, which strict mode also refuses.
+
+use anyhow::{anyhow, Result};
+use manifest_contract as mc;
+
+use crate::pml_render::Citations;
+use crate::ssg::{Args, ArtifactViewExt};
+
+/// Check the artifacts against what the `rendered` Proofdown pages of this build cited.
+pub(crate) fn check(args: &Args, manifest: &mc::Manifest, views: &[ArtifactViewExt], citations: &Citations, rendered: usize) -> Result<()> {
+    if rendered == 0 {
+        if args.strict_evidence_coverage {
+            return Err(anyhow!(
+                "evidence coverage check failed: no page was rendered from Proofdown (set front_page.ast or pages[].ast, or build with the external_pml feature)"
+            ));
+        }
+        return Ok(());
+    }
+    // Page sources are not evidence: the AST exports and Proofdown artifacts (pages of their own)
+    let exports: Vec<&str> = manifest.front_page.ast.iter().chain(manifest.pages.iter().filter_map(|p| p.ast.as_ref())).map(String::as_str).collect();
+    let exempt: Vec<&str> = views
+        .iter()
+        .map(|v| &v.artifact)
+        .filter(|a| a.render == "proofdown" || exports.contains(&a.id.as_str()))
+        .map(|a| a.id.as_str())
+        .collect();
+    let issues = citations.coverage_issues(views, &exempt);
+    for issue in &issues {
+        eprintln!("evidence coverage: {}", issue);
+    }
+    if args.strict_evidence_coverage && !issues.is_empty() {
+        return Err(anyhow!("evidence coverage check failed:\n  {}", issues.join("\n  ")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssg::{run_with_args, Args};
+    use crate::ssg::test_support::*;
+
+    #[test]
+    fn reports_evidence_coverage_of_the_front_page() {
+        let root = example_copy();
+        let set_front_page = |ast: &[u8]| set_front_page_ast(&root, ast);
+        let args = |strict: bool| Args {
+            strict_evidence_coverage: strict,
+            ..test_args(root.clone(), unique_out())
+        };
+
+        set_front_page(br#"{"blocks": [
+            {"type": "paragraph", "inlines": [{"type": "text", "text": "See [[a:failures]]."}]},
+            {"type": "component", "name": "artifact.table", "attrs": [{"key": "id", "value": "coverage"}]}
+        ]}"#);
+        run_with_args(args(false)).expect("coverage issues are only reported by default");
+        let err = run_with_args(args(true)).unwrap_err().to_string();
+        assert!(err.contains("artifact tests-summary (summary:test) is not referenced by any Proofdown page"), "{}", err);
+        assert!(!err.contains("failures"), "links count as references: {}", err);
+        assert!(!err.contains("artifact coverage"), "{}", err);
+        assert!(!err.contains("front-page-ast"), "the front page export is exempt: {}", err);
+
+        // A component that cannot show the artifact's render hint fails before the artifact is read
+        set_front_page(br#"{"blocks": [{"type": "component", "name": "artifact.markdown", "attrs": [{"key": "id", "value": "coverage"}]}]}"#);
+        let err = run_with_args(args(false)).unwrap_err().to_string();
+        assert_eq!(err, "<artifact.markdown> cannot show coverage (render: table:coverage); expected markdown");
+    }
+
+    #[cfg(not(feature = "external_pml"))]
+    #[test]
+    fn strict_coverage_needs_a_rendered_page() {
+        // The example has no AST export, so this build shows the built-in overview
+        let args = |strict: bool| Args {
+            strict_evidence_coverage: strict,
+            ..test_args(example(), unique_out())
+        };
+        run_with_args(args(false)).expect("nothing to check is fine by default");
+        let err = run_with_args(args(true)).unwrap_err().to_string();
+        assert!(err.starts_with("evidence coverage check failed: no page was rendered from Proofdown"), "{}", err);
+    }
+}
@@ -0,0 +1,187 @@
+// Single-file HTML export: one self-contained document (inline CSS, images and small artifacts
+// as data URIs, one anchored section per artifact) for attaching evidence to tickets and emails.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use manifest_contract as mc;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::proofdown::esc;
+use crate::render::EvidenceLink;
+use crate::theme;
+use crate::ssg::{evidence_links, json_scalar, load_verified, render_artifact_body, Args, ArtifactViewExt, Verified};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ExportHtmlArgs {
+    #[command(flatten)]
+    pub build: Args,
+
+    /// Output path of the self-contained HTML file
+    #[arg(long, default_value = "evidence.html")]
+    pub file: PathBuf,
+}
+
+pub fn export_html(opts: ExportHtmlArgs) -> Result<()> {
+    let html = render_single_file(&opts.build)?;
+    if let Some(parent) = opts.file.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    fs::write(&opts.file, html).with_context(|| format!("write {}", opts.file.display()))?;
+    println!("Evidence exported to {}", opts.file.display());
+    Ok(())
+}
+
+pub(crate) fn render_single_file(args: &Args) -> Result<String> {
+    let Verified { manifest, manifest_json, provenance_verified, mut views, kpis, title, .. } = load_verified(args)?;
+
+    // Artifacts up to the inline limit travel inside the document; larger ones keep an empty href
+    // and their section shows a card with size and digest instead of the body.
+    for v in &mut views {
+        let src = args.root.join(&v.artifact.path);
+        v.download_href = match fs::read(&src) {
+            Ok(bytes) if bytes.len() <= args.truncate_inline_bytes => {
+                format!("data:{};base64,{}", data_media_type(&v.artifact.media_type), B64.encode(&bytes))
+            }
+            _ => String::new(),
+        };
+    }
+
+    let manifest_digest = format!("{:x}", Sha256::digest(mc::canonicalize(&manifest_json)));
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "<header class=\"page\"><h1>{}</h1><p class=\"muted\">{} @ {}</p></header>",
+        esc(&title),
+        esc(&manifest.repo),
+        esc(&manifest.commit)
+    ));
+
+    // Verification report
+    out.push_str("<section id=\"verification\"><h2>Verification</h2><dl class=\"meta\">");
+    out.push_str(&format!("<dt>Manifest SHA-256</dt><dd><code>{}</code></dd>", manifest_digest));
+    out.push_str(&format!(
+        "<dt>Signature</dt><dd>{}</dd>",
+        if provenance_verified { "<span class=\"badge ok\">verified</span> Ed25519" } else { "<span class=\"badge warn\">not checked</span> build without --verify-manifest" }
+    ));
+    out.push_str(&format!(
+        "<dt>Workflow run</dt><dd><a href=\"{}\">{}</a> (attempt {})</dd>",
+        esc(&manifest.workflow_run.url),
+        esc(&json_scalar(&manifest.workflow_run.id)),
+        manifest.workflow_run.attempt
+    ));
+    out.push_str("</dl>");
+    out.push_str("<table><thead><tr><th scope=\"col\">ID</th><th scope=\"col\">Expected SHA-256</th><th scope=\"col\">Computed SHA-256</th><th scope=\"col\">Status</th></tr></thead><tbody>");
+    for v in &views {
+        out.push_str(&format!(
+            "<tr><th scope=\"row\"><a href=\"#a-{}\">{}</a></th><td><code>{}</code></td><td><code>{}</code></td><td>{}</td></tr>",
+            esc(&v.artifact.id),
+            esc(&v.artifact.id),
+            esc(&v.artifact.sha256),
+            esc(v.digest_hex.as_deref().unwrap_or("missing")),
+            status_badge(v.verified)
+        ));
+    }
+    out.push_str("</tbody></table></section>");
+
+    if !kpis.is_empty() {
+        out.push_str("<div class=\"cards\">");
+        for (k, v) in kpis.iter() {
+            out.push_str(&format!("<div class=\"card\"><h3>{}</h3><p><strong>{}</strong></p></div>", esc(k), esc(v)));
+        }
+        out.push_str("</div>");
+    }
+
+    // Per-artifact sections
+    let evidence = evidence_links(&views, |id| format!("#a-{}", id));
+    for v in &views {
+        out.push_str(&render_section(v, args, &evidence)?);
+    }
+
+    Ok(format!(
+        "<!doctype html><html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><title>{}</title><style>{}</style></head><body><div class=\"container\"><main id=\"main\" role=\"main\">{}</main></div></body></html>",
+        esc(&title),
+        theme::site_css(args.theme.as_deref())?,
+        out
+    ))
+}
+
+fn render_section(v: &ArtifactViewExt, args: &Args, evidence: &BTreeMap<String, EvidenceLink>) -> Result<String> {
+    let a = &v.artifact;
+    let mut out = String::new();
+    out.push_str(&format!("<section id=\"a-{}\"><h2>{}</h2>", esc(&a.id), esc(&a.title)));
+    out.push_str(&format!(
+        "<p class=\"muted\">{} · {} · {}</p><p>{}</p>",
+        esc(&a.id),
+        esc(&a.render),
+        esc(&a.media_type),
+        status_badge(v.verified)
+    ));
+    if v.download_href.is_empty() {
+        let size = match fs::metadata(args.root.join(&a.path)) {
+            Ok(m) => format!("{} bytes", m.len()),
+            Err(_) => "file missing".to_string(),
+        };
+        out.push_str(&format!(
+            "<div class=\"card\"><strong>Not embedded</strong> ({}, SHA-256 <code>{}</code>): larger than {} bytes. Source path: <code>{}</code></div>",
+            size,
+            esc(&a.sha256),
+            args.truncate_inline_bytes,
+            esc(&a.path)
+        ));
+    } else {
+        let body = render_artifact_body(v, &args.root, args.truncate_inline_bytes, evidence)?;
+        out.push_str(&format!("<article>{}</article>", body));
+        let file_name = a.path.rsplit('/').next().unwrap_or(&a.id);
+        out.push_str(&format!("<p><a href=\"{}\" download=\"{}\">Download embedded file</a></p>", v.download_href, esc(file_name)));
+    }
+    out.push_str("<p><a href=\"#verification\">Back to verification report</a></p></section>");
+    Ok(out)
+}
+
+fn status_badge(verified: bool) -> &'static str {
+    if verified { "<span class=\"badge ok\">verified</span>" } else { "<span class=\"badge err\">digest mismatch</span>" }
+}
+
+/// Media type for a `data:` URI: the declared type without parameters, or
+/// `application/octet-stream` when it holds anything but token characters.
+fn data_media_type(media_type: &str) -> String {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    let safe = essence.split('/').count() == 2 && essence.chars().all(|c| c.is_ascii_alphanumeric() || "/.+-_".contains(c));
+    if safe { essence.to_ascii_lowercase() } else { "application/octet-stream".to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssg::test_support::*;
+
+    #[test]
+    fn single_file_is_self_contained() {
+        let args = test_args(example(), std::env::temp_dir());
+        let html = render_single_file(&args).expect("export succeeds");
+        assert!(html.contains("<style>"));
+        assert!(!html.contains("href=\"/"), "no root-absolute links in single-file export");
+        assert!(html.contains("id=\"a-tests-summary\""));
+        assert!(html.contains("href=\"data:text/markdown;base64,"));
+        assert!(html.contains("Manifest SHA-256"));
+    }
+
+    #[test]
+    fn oversized_artifacts_get_a_card_instead_of_a_body() {
+        let args = Args { truncate_inline_bytes: 1, ..test_args(example(), std::env::temp_dir()) };
+        let html = render_single_file(&args).expect("export succeeds");
+        assert!(html.contains("<strong>Not embedded</strong> ("), "{}", html);
+        assert!(!html.contains("data:"));
+        assert!(!html.contains("Download"), "no download link without an embedded file");
+    }
+
+    #[test]
+    fn data_uri_media_type_is_sanitized() {
+        assert_eq!(data_media_type("application/json; charset=utf-8"), "application/json");
+        assert_eq!(data_media_type("Image/SVG+XML"), "image/svg+xml");
+        assert_eq!(data_media_type("text/html\" onload=\"x"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,193 @@
+// Discovery outputs: `robots.txt`, `sitemap.xml` and an Atom `feed.xml` with one entry per build.
+// The sitemap and feed need absolute URLs, so they are only written when `--site-url` is configured.
+// Timestamps come from SOURCE_DATE_EPOCH (reproducible builds convention) or the commit time of
+// the manifest's commit, never the wall clock; without either the feed is not written.
+
+use anyhow::{Context, Result};
+use manifest_contract as mc;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::ssg::{ArtifactViewExt, Site};
+
+/// Write `robots.txt`, and `sitemap.xml` and `feed.xml` when the site URL (and for the feed, the
+/// build time `feed_epoch`) is known.
+pub(crate) fn publish(
+    site: &Site,
+    manifest: &mc::Manifest,
+    manifest_json: &serde_json::Value,
+    views: &[ArtifactViewExt],
+    kpis: &BTreeMap<&'static str, String>,
+    feed_epoch: Option<i64>,
+) -> Result<()> {
+    let out = &site.args.out;
+    let mut robots = format!("User-agent: *\nDisallow: {}/fragment/\n", site.base);
+    if let Some(sitemap) = site.abs("/sitemap.xml") {
+        robots.push_str(&format!("Sitemap: {}\n", sitemap));
+    }
+    fs::write(out.join("robots.txt"), robots).ok();
+
+    let (Some(home), Some(feed_url)) = (site.abs("/"), site.abs("/feed.xml")) else {
+        return Ok(());
+    };
+    let mut urls = vec![home.clone()];
+    urls.extend(site.abs("/artifacts/"));
+    urls.extend(site.pages.iter().filter_map(|(p, _)| site.abs(&format!("/p/{}/", p.id))));
+    urls.extend(views.iter().filter_map(|v| site.abs(&format!("/a/{}/", v.artifact.id))));
+    fs::write(out.join("sitemap.xml"), sitemap_xml(&urls)).context("write sitemap.xml")?;
+
+    if let Some(updated_epoch) = feed_epoch {
+        let mut summary_lines = vec![
+            format!("Commit: {}", site.at_commit),
+            format!("Artifacts: {} ({} verified)", views.len(), views.iter().filter(|v| v.verified).count()),
+        ];
+        summary_lines.extend(kpi_lines(kpis));
+        let entry = FeedEntry {
+            id: format!("urn:sha256:{:x}", Sha256::digest(mc::canonicalize(manifest_json))),
+            title: &site.title,
+            link: home.clone(),
+            summary_lines,
+            updated_epoch,
+        };
+        let feed_title = format!("Provenance evidence for {}", manifest.repo);
+        fs::write(out.join("feed.xml"), atom_feed(&feed_title, &home, &feed_url, &entry)).context("write feed.xml")?;
+    }
+    Ok(())
+}
+
+/// Build timestamp in seconds since the Unix epoch: `SOURCE_DATE_EPOCH` if set, else the committer
+/// time of `commit` in the git checkout at `root`. `None` when neither is available.
+pub(crate) fn build_epoch(root: &Path, commit: &str) -> Option<i64> {
+    if let Some(epoch) = std::env::var("SOURCE_DATE_EPOCH").ok().and_then(|s| s.trim().parse().ok()) {
+        return Some(epoch);
+    }
+    if commit.is_empty() || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let out = Command::new("git").arg("-C").arg(root).args(["show", "-s", "--format=%ct"]).arg(format!("{}^{{commit}}", commit)).output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    String::from_utf8(out.stdout).ok()?.trim().parse().ok()
+}
+
+/// Normalize `--site-url` to an origin without trailing slash, e.g. "https://host".
+pub(crate) fn normalize_site_url(raw: &str) -> Option<String> {
+    let s = raw.trim().trim_end_matches('/');
+    if s.is_empty() { None } else { Some(s.to_string()) }
+}
+
+pub(crate) fn sitemap_xml(urls: &[String]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for u in urls {
+        out.push_str(&format!("  <url><loc>{}</loc></url>\n", xml_escape(u)));
+    }
+    out.push_str("</urlset>\n");
+    out
+}
+
+pub(crate) struct FeedEntry<'a> {
+    /// Stable, unique id for this build (e.g. `urn:sha256:<canonical manifest digest>`)
+    pub id: String,
+    pub title: &'a str,
+    pub link: String,
+    pub summary_lines: Vec<String>,
+    pub updated_epoch: i64,
+}
+
+pub(crate) fn atom_feed(feed_title: &str, site_home: &str, feed_url: &str, entry: &FeedEntry) -> String {
+    let updated = rfc3339_utc(entry.updated_epoch);
+    let summary = entry.summary_lines.join("\n");
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n  <title>{}</title>\n  <id>{}</id>\n  <link rel=\"self\" href=\"{}\"/>\n  <link href=\"{}\"/>\n  <updated>{}</updated>\n  <entry>\n    <title>{}</title>\n    <id>{}</id>\n    <link href=\"{}\"/>\n    <updated>{}</updated>\n    <author><name>provenance-ssg</name></author>\n    <summary>{}</summary>\n  </entry>\n</feed>\n",
+        xml_escape(feed_title),
+        xml_escape(feed_url),
+        xml_escape(feed_url),
+        xml_escape(site_home),
+        updated,
+        xml_escape(entry.title),
+        xml_escape(&entry.id),
+        xml_escape(&entry.link),
+        updated,
+        xml_escape(&summary)
+    )
+}
+
+/// Feed summary lines from the index KPIs, in their deterministic (sorted) order.
+pub(crate) fn kpi_lines(kpis: &BTreeMap<&str, String>) -> Vec<String> {
+    kpis.iter().map(|(k, v)| format!("{}: {}", k, v)).collect()
+}
+
+fn rfc3339_utc(epoch: i64) -> String {
+    let days = epoch.div_euclid(86_400);
+    let secs = epoch.rem_euclid(86_400);
+    // Civil-from-days (Howard Hinnant), proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", y, m, d, secs / 3600, (secs % 3600) / 60, secs % 60)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssg::test_support::*;
+    use crate::ssg::{run_with_args, Args};
+
+    #[test]
+    fn formats_epoch_as_rfc3339() {
+        assert_eq!(rfc3339_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339_utc(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(rfc3339_utc(951_782_400), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn writes_sitemap_feed_and_meta_with_site_url() {
+        let out = unique_out();
+        let args = Args {
+            base_path: "/proj".into(),
+            site_url: Some("https://evidence.example.com/".into()),
+            ..test_args(example(), out.clone())
+        };
+        run_with_args(args.clone()).expect("site generation succeeds");
+        let sitemap = std::fs::read_to_string(out.join("sitemap.xml")).expect("read sitemap");
+        assert!(sitemap.contains("<loc>https://evidence.example.com/proj/a/coverage/</loc>"));
+        let page = std::fs::read_to_string(out.join("a").join("coverage").join("index.html")).expect("read page");
+        assert!(page.contains("<title>Coverage — "));
+        assert!(page.contains("<meta property=\"og:image\" content=\"https://evidence.example.com/proj/badge/provenance.svg\"/>"));
+        let robots = std::fs::read_to_string(out.join("robots.txt")).expect("read robots");
+        assert!(robots.contains("Sitemap: https://evidence.example.com/proj/sitemap.xml"));
+
+        // The example commit is not in any checkout, so the feed is dated explicitly
+        let (manifest, manifest_json) = mc::load_manifest(args.root.join(&args.manifest)).unwrap();
+        let site = Site {
+            args: &args,
+            base: "/proj".into(),
+            site_url: Some("https://evidence.example.com".into()),
+            feed: true,
+            title: "QA Evidence".into(),
+            at_commit: format!("{}@{}", manifest.repo, manifest.commit),
+            pages: Vec::new(),
+        };
+        let kpis = BTreeMap::from([("Tests", "12 passed".to_string())]);
+        publish(&site, &manifest, &manifest_json, &[], &kpis, Some(1_700_000_000)).expect("publish succeeds");
+        let feed = std::fs::read_to_string(out.join("feed.xml")).expect("read feed");
+        assert_eq!(feed.matches("<entry>").count(), 1);
+        assert!(feed.contains("<updated>2023-11-14T22:13:20Z</updated>"));
+        assert!(feed.contains("<link href=\"https://evidence.example.com/proj/\"/>"), "{}", feed);
+        assert!(feed.contains("Tests: 12 passed"), "{}", feed);
+    }
+}
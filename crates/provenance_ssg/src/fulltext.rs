@@ -0,0 +1,201 @@
+// Full-text search over artifact contents. Each verified artifact is cut into short passages
+// (Markdown blocks, JSON leaves as "path: value", lines of text); every token maps to the first
+// passage it occurs in per artifact. Whole passages are indexed; only the stored snippets are
+// clipped. Postings are sharded by a hash of the token so the results page (assets/fulltext.js)
+// only fetches the shards its query needs:
+//
+//   search/meta.json              {version, shards, docs: [{id, title, render}]}
+//   search/shard-XX.json          {token: [[doc, passage], ...]}
+//   search/passages/<id>.json     [passage, ...]
+//
+// The tokenizer and shard hash must stay in sync with `render::fulltext_js`.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::render;
+
+pub(crate) const SHARDS: u32 = 16;
+const MAX_PASSAGE_CHARS: usize = 200;
+const MAX_PASSAGES_PER_DOC: usize = 5000;
+const MIN_TOKEN_CHARS: usize = 2;
+const MAX_TOKEN_CHARS: usize = 40;
+
+pub(crate) struct Doc<'a> {
+    pub id: &'a str,
+    pub title: &'a str,
+    pub render: &'a str,
+    /// Full passage text; clipped to `MAX_PASSAGE_CHARS` only when written out as snippets
+    pub passages: Vec<String>,
+}
+
+/// Passages for an artifact, by render hint. Binary kinds (images) have none.
+pub(crate) fn passages_for(render: &str, bytes: &[u8]) -> Vec<String> {
+    let mut out = match render {
+        "markdown" | "proofdown" => markdown_passages(&String::from_utf8_lossy(bytes)),
+        "json" | "summary:test" | "table:coverage" | "llm-proof" => match serde_json::from_slice::<Value>(bytes) {
+            Ok(v) => {
+                let mut acc = Vec::new();
+                json_passages(&v, String::new(), &mut acc);
+                acc
+            }
+            Err(_) => Vec::new(),
+        },
+        // Decoded as the `text` viewer shows it; binary data has nothing to index
+        "text" => match render::decode_text(bytes) {
+            Some((_, text)) => text.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect(),
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    out.truncate(MAX_PASSAGES_PER_DOC);
+    out
+}
+
+/// Text of each Markdown block (paragraph, heading, list item, table cell, code block), whitespace collapsed.
+pub(crate) fn markdown_passages(md: &str) -> Vec<String> {
+    use pulldown_cmark::{Event, Parser, Tag};
+    fn is_block(tag: &Tag) -> bool {
+        matches!(tag, Tag::Paragraph | Tag::Heading(..) | Tag::Item | Tag::TableCell | Tag::CodeBlock(_) | Tag::BlockQuote)
+    }
+    let mut out = Vec::new();
+    let mut cur = String::new();
+    let mut flush = |cur: &mut String| {
+        let collapsed = cur.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !collapsed.is_empty() {
+            out.push(collapsed);
+        }
+        cur.clear();
+    };
+    for ev in Parser::new_ext(md, pulldown_cmark::Options::ENABLE_TABLES) {
+        match ev {
+            Event::Text(t) | Event::Code(t) => cur.push_str(&t),
+            Event::SoftBreak | Event::HardBreak => cur.push(' '),
+            // Inline tags (emphasis, links) stay glued to their words; blocks become passages
+            Event::Start(tag) | Event::End(tag) if is_block(&tag) => flush(&mut cur),
+            _ => {}
+        }
+    }
+    flush(&mut cur);
+    out
+}
+
+fn json_passages(v: &Value, path: String, out: &mut Vec<String>) {
+    match v {
+        Value::Object(map) => {
+            for (k, child) in map {
+                let p = if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) };
+                json_passages(child, p, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                json_passages(child, format!("{}[{}]", path, i), out);
+            }
+        }
+        Value::Null => {}
+        Value::String(s) => out.push(format!("{}: {}", path, s)),
+        other => out.push(format!("{}: {}", path, other)),
+    }
+}
+
+fn clip(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}…", s[..cut].trim_end()),
+        None => s.to_string(),
+    }
+}
+
+/// Lowercased runs of letters, digits and `_`, between 2 and 40 characters long.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|t| (MIN_TOKEN_CHARS..=MAX_TOKEN_CHARS).contains(&t.chars().count()))
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// FNV-1a (32-bit) over the token's UTF-8 bytes, modulo `SHARDS`.
+pub(crate) fn shard_of(token: &str) -> u32 {
+    let mut h: u32 = 0x811c_9dc5;
+    for b in token.bytes() {
+        h ^= b as u32;
+        h = h.wrapping_mul(0x0100_0193);
+    }
+    h % SHARDS
+}
+
+/// Write `search/` (see module docs). Output is deterministic for a given set of docs.
+pub(crate) fn write_index(out_dir: &Path, docs: &[Doc]) -> Result<()> {
+    let dir = out_dir.join("search");
+    let passages_dir = dir.join("passages");
+    fs::create_dir_all(&passages_dir).with_context(|| format!("create {}", passages_dir.display()))?;
+
+    let mut shards: Vec<BTreeMap<String, Vec<[usize; 2]>>> = (0..SHARDS).map(|_| BTreeMap::new()).collect();
+    for (d, doc) in docs.iter().enumerate() {
+        let mut seen = std::collections::BTreeSet::new();
+        for (p, passage) in doc.passages.iter().enumerate() {
+            for tok in tokenize(passage) {
+                if seen.insert(tok.clone()) {
+                    shards[shard_of(&tok) as usize].entry(tok).or_default().push([d, p]);
+                }
+            }
+        }
+        let snippets: Vec<String> = doc.passages.iter().map(|p| clip(p, MAX_PASSAGE_CHARS)).collect();
+        let txt = serde_json::to_string(&snippets)? + "\n";
+        fs::write(passages_dir.join(format!("{}.json", doc.id)), txt).context("write search passages")?;
+    }
+
+    for (i, shard) in shards.iter().enumerate() {
+        let txt = serde_json::to_string(shard)? + "\n";
+        fs::write(dir.join(format!("shard-{:02x}.json", i)), txt).context("write search shard")?;
+    }
+
+    let meta = serde_json::json!({
+        "version": 1,
+        "shards": SHARDS,
+        "docs": docs.iter().map(|d| serde_json::json!({"id": d.id, "title": d.title, "render": d.render})).collect::<Vec<_>>(),
+    });
+    fs::write(dir.join("meta.json"), serde_json::to_string_pretty(&meta)? + "\n").context("write search meta")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_leaves_become_path_value_passages() {
+        let bytes = br#"{"files":[{"path":"src/lib.rs","pct":92.1}],"total":{"pct":85.2}}"#;
+        let p = passages_for("table:coverage", bytes);
+        assert_eq!(p, vec!["files[0].path: src/lib.rs", "files[0].pct: 92.1", "total.pct: 85.2"]);
+        assert_eq!(tokenize(&p[0]), vec!["files", "path", "src", "lib", "rs"]);
+    }
+
+    #[test]
+    fn text_is_decoded_like_the_text_viewer() {
+        assert_eq!(passages_for("text", b"\xFF\xFEo\x00k\x00\n\x00"), vec!["ok"]);
+        assert_eq!(passages_for("text", b"caf\xE9\n"), vec!["café"]);
+        assert!(passages_for("text", b"\x7FELF\x02\x01\x00").is_empty());
+    }
+
+    #[test]
+    fn words_past_the_snippet_length_are_indexed() {
+        let out = std::env::temp_dir().join(format!("prov-fulltext-{}", std::process::id()));
+        let passage = format!("{} needle", "hay ".repeat(MAX_PASSAGE_CHARS));
+        let doc = Doc { id: "log", title: "Log", render: "text", passages: passages_for("text", passage.as_bytes()) };
+        write_index(&out, &[doc]).unwrap();
+        let shard = fs::read_to_string(out.join("search").join(format!("shard-{:02x}.json", shard_of("needle")))).unwrap();
+        assert!(shard.contains("\"needle\":[[0,0]]"), "{}", shard);
+        let snippets: Vec<String> = serde_json::from_str(&fs::read_to_string(out.join("search/passages/log.json")).unwrap()).unwrap();
+        assert!(snippets[0].ends_with('…') && !snippets[0].contains("needle"));
+    }
+
+    #[test]
+    fn shard_hash_is_stable() {
+        // FNV-1a("timeout") = 0x97f68388; fulltext.js must compute the same shard in the browser.
+        assert_eq!(shard_of("timeout"), 8);
+    }
+}
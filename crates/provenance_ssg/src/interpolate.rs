@@ -0,0 +1,114 @@
+// `{{ name }}` placeholders in the front page, page and artifact titles and in Proofdown text.
+// Values come only from the verified manifest and the KPI artifacts; there are no expressions,
+// filters or includes, and an unknown or malformed placeholder is a build error rather than
+// silently left in the output.
+
+use anyhow::{bail, Result};
+use manifest_contract as mc;
+use std::collections::BTreeMap;
+
+use crate::render::{Coverage, TestSummary};
+use crate::ssg::{json_scalar, ArtifactViewExt};
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Vars {
+    values: BTreeMap<String, String>,
+}
+
+impl Vars {
+    /// Context for a build: `repo`, `commit`, `short_commit`, `workflow_run.{id,url,attempt}`,
+    /// `artifacts.{count,verified}`, plus `tests.*` and `coverage.pct` when those artifacts parse.
+    /// `front_page.title` is the interpolated title itself.
+    pub(crate) fn for_build(
+        manifest: &mc::Manifest,
+        views: &[ArtifactViewExt],
+        tests: Option<&TestSummary>,
+        coverage: Option<&Coverage>,
+    ) -> Result<Vars> {
+        let mut v = Vars::default();
+        v.set("repo", manifest.repo.clone());
+        v.set("commit", manifest.commit.clone());
+        v.set("short_commit", manifest.commit.chars().take(7).collect());
+        v.set("workflow_run.id", json_scalar(&manifest.workflow_run.id));
+        v.set("workflow_run.url", manifest.workflow_run.url.clone());
+        v.set("workflow_run.attempt", manifest.workflow_run.attempt.to_string());
+        v.set("artifacts.count", views.len().to_string());
+        v.set("artifacts.verified", views.iter().filter(|a| a.verified).count().to_string());
+        if let Some(t) = tests {
+            v.set("tests.total", t.total.to_string());
+            v.set("tests.passed", t.passed.to_string());
+            v.set("tests.failed", t.failed.to_string());
+            v.set("tests.duration_seconds", format!("{:.2}", t.duration_seconds));
+        }
+        if let Some(total) = coverage.and_then(|c| c.total.as_ref()) {
+            v.set("coverage.pct", format!("{:.1}", total.pct));
+        }
+        let title = v.render(&manifest.front_page.title)?;
+        v.set("front_page.title", title);
+        Ok(v)
+    }
+
+    fn set(&mut self, name: &str, value: String) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Replace every `{{ name }}` in `template`. Output is plain text; callers escape it for HTML.
+    pub(crate) fn render(&self, template: &str) -> Result<String> {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                bail!("unterminated placeholder in {:?}", template);
+            };
+            let name = after[..end].trim();
+            let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.');
+            if !valid {
+                bail!("malformed placeholder `{{{{{}}}}}` in {:?}", &after[..end], template);
+            }
+            match self.get(name) {
+                Some(value) => out.push_str(value),
+                None => bail!(
+                    "unknown placeholder `{{{{ {} }}}}` in {:?} (known: {})",
+                    name,
+                    template,
+                    self.values.keys().map(String::as_str).collect::<Vec<_>>().join(", ")
+                ),
+            }
+            rest = &after[end + 2..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> Vars {
+        let mut v = Vars::default();
+        v.set("commit", "deadbeefcafe".into());
+        v.set("tests.passed", "41".into());
+        v
+    }
+
+    #[test]
+    fn replaces_known_placeholders() {
+        assert_eq!(vars().render("QA for {{ commit }}: {{tests.passed}} passed").unwrap(), "QA for deadbeefcafe: 41 passed");
+        assert_eq!(vars().render("no placeholders").unwrap(), "no placeholders");
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_placeholders() {
+        let err = vars().render("{{ coverage.pct }}").unwrap_err().to_string();
+        assert!(err.contains("unknown placeholder `{{ coverage.pct }}`"), "{}", err);
+        assert!(vars().render("{{ commit").is_err());
+        assert!(vars().render("{{ commit | upper }}").is_err());
+    }
+}
@@ -1,4 +1,19 @@
+pub mod bundle;
+mod coverage;
+pub mod export;
+mod feeds;
+mod fulltext;
+mod interpolate;
+mod links;
+pub mod lint;
+mod pages;
+mod pml_ast;
+mod pml_render;
+mod proofdown;
 mod render;
+mod search;
+pub mod serve;
 pub mod ssg;
+mod theme;
 
-pub use ssg::{run_with_args, Args};
+pub use ssg::{run_with_args, Args, Cli, Command};
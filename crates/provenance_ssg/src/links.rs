@@ -0,0 +1,309 @@
+// Proofdown `[[target]]` / `[[target | label]]` link macros (`.specs/10_proofdown.md` §3.3, §15).
+// Targets resolve only against the verified manifest; anything that does not resolve fails the build.
+//
+//   a:<id>                          artifact page
+//   repo:<path>[#Ln[-Lm]], src:..   `repo:file` artifact page, or a commit-pinned GitHub blob URL
+//                                   when the path is inside a verified `repo:bundle` archive
+//   <path>[#Ln[-Lm]]                shorthand for repo:
+//   sym:<path>::<name>              location from a verified `repo:symbols` artifact, then as repo:
+//   doc:#<anchor>                   in-page anchor
+//   gh:issue:<n>, gh:pr:<n>         GitHub issue/PR of the manifest repo (anchor only, never fetched)
+//   ci:run, ci:job:<name>           the manifest's workflow run (it records no job URLs, so a job
+//                                   links to its run, labelled with the job name)
+
+use anyhow::{anyhow, bail, Context, Result};
+use manifest_contract as mc;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::bundle::read_tar;
+use crate::interpolate::Vars;
+use crate::proofdown::esc;
+use crate::ssg::{json_scalar, ArtifactViewExt};
+
+/// A bare repo path, optionally with a line range
+static REPO_TARGET: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Za-z0-9._\-/]+(?:#L\d+(?:-L\d+)?)?$").unwrap());
+static REPO_PATH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Za-z0-9._\-/]+$").unwrap());
+static LINE_RANGE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^L\d+(?:-L\d+)?$").unwrap());
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Link {
+    pub href: String,
+    pub label: String,
+    /// Set when the link lands on an artifact page
+    pub artifact: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Symbol {
+    name: String,
+    path: String,
+    line: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct LinkIndex {
+    base: String,
+    repo: String,
+    commit: String,
+    run_id: String,
+    run_url: String,
+    /// artifact id -> title
+    artifacts: BTreeMap<String, String>,
+    /// repo path -> id of its `repo:file` artifact
+    files: BTreeMap<String, String>,
+    /// repo paths inside verified `repo:bundle` archives
+    bundled: BTreeSet<String>,
+    symbols: Vec<Symbol>,
+}
+
+impl LinkIndex {
+    /// Reads verified `repo:bundle` (tar or tar.gz) and `repo:symbols` artifacts; unverified ones are ignored.
+    pub(crate) fn build(manifest: &mc::Manifest, views: &[ArtifactViewExt], root: &Path, base: &str) -> Result<LinkIndex> {
+        let mut idx = LinkIndex {
+            base: base.to_string(),
+            repo: manifest.repo.clone(),
+            commit: manifest.commit.clone(),
+            run_id: json_scalar(&manifest.workflow_run.id),
+            run_url: manifest.workflow_run.url.clone(),
+            ..LinkIndex::default()
+        };
+        for v in views {
+            let a = &v.artifact;
+            idx.artifacts.insert(a.id.clone(), a.title.clone());
+            match a.render.as_str() {
+                "repo:file" => {
+                    idx.files.insert(a.path.clone(), a.id.clone());
+                }
+                "repo:bundle" if v.verified => {
+                    let bytes = fs::read(root.join(&a.path)).with_context(|| format!("read repo bundle {}", a.path))?;
+                    idx.bundled.extend(bundle_paths(&bytes).with_context(|| format!("read repo bundle {}", a.path))?);
+                }
+                "repo:symbols" if v.verified => {
+                    let bytes = fs::read(root.join(&a.path)).with_context(|| format!("read symbols {}", a.path))?;
+                    idx.symbols.extend(parse_symbols(&bytes).with_context(|| format!("parse symbols {}", a.path))?);
+                }
+                _ => {}
+            }
+        }
+        Ok(idx)
+    }
+
+    /// Text with every macro replaced by an anchor. Plain text and labels are interpolated and escaped;
+    /// `cite` gets the id of every artifact a link lands on.
+    pub(crate) fn expand(&self, text: &str, vars: &Vars, cite: &dyn Fn(&str)) -> Result<String> {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("[[") {
+            out.push_str(&esc(&vars.render(&rest[..start])?));
+            let after = &rest[start + 2..];
+            let end = after.find("]]").ok_or_else(|| anyhow!("unterminated link macro in {:?}", text))?;
+            let (target, label) = match after[..end].split_once('|') {
+                Some((t, l)) => (t.trim(), Some(l.trim()).filter(|l| !l.is_empty())),
+                None => (after[..end].trim(), None),
+            };
+            let link = self.resolve(target).with_context(|| format!("in link [[{}]]", &after[..end]))?;
+            if let Some(id) = &link.artifact {
+                cite(id);
+            }
+            let label = match label {
+                Some(l) => vars.render(l)?,
+                None => link.label,
+            };
+            out.push_str(&format!("<a href=\"{}\">{}</a>", esc(&link.href), esc(&label)));
+            rest = &after[end + 2..];
+        }
+        out.push_str(&esc(&vars.render(rest)?));
+        Ok(out)
+    }
+
+    pub(crate) fn resolve(&self, target: &str) -> Result<Link> {
+        if let Some(id) = target.strip_prefix("a:") {
+            let title = self.artifacts.get(id).ok_or_else(|| anyhow!("unknown artifact id: {}", id))?;
+            return Ok(Link { href: format!("{}/a/{}/", self.base, id), label: title.clone(), artifact: Some(id.to_string()) });
+        }
+        if let Some(spec) = target.strip_prefix("repo:").or_else(|| target.strip_prefix("src:")) {
+            return self.repo_link(spec, None);
+        }
+        if let Some(anchor) = target.strip_prefix("doc:#") {
+            if !is_ident(anchor) {
+                bail!("invalid anchor: {:?}", anchor);
+            }
+            return Ok(Link { href: format!("#{}", anchor), label: anchor.to_string(), artifact: None });
+        }
+        if let Some(n) = target.strip_prefix("gh:issue:") {
+            return self.github(n, "issues", format!("issue #{}", n));
+        }
+        if let Some(n) = target.strip_prefix("gh:pr:") {
+            return self.github(n, "pull", format!("PR #{}", n));
+        }
+        if target == "ci:run" {
+            return Ok(Link { href: self.run_url.clone(), label: format!("CI run {}", self.run_id), artifact: None });
+        }
+        if let Some(job) = target.strip_prefix("ci:job:") {
+            if !is_ident(job) {
+                bail!("invalid job name: {:?}", job);
+            }
+            return Ok(Link { href: self.run_url.clone(), label: job.to_string(), artifact: None });
+        }
+        if let Some(sym) = target.strip_prefix("sym:") {
+            return self.symbol_link(sym);
+        }
+        if REPO_TARGET.is_match(target) {
+            return self.repo_link(target, None);
+        }
+        bail!("invalid link target {:?} (expected a:, repo:, src:, sym:, doc:#, gh:issue:, gh:pr:, ci:run, ci:job: or a repo path)", target)
+    }
+
+    fn repo_link(&self, spec: &str, label: Option<String>) -> Result<Link> {
+        let (path, lines) = match spec.split_once('#') {
+            Some((p, frag)) => (p, Some(frag)),
+            None => (spec, None),
+        };
+        let path_ok = REPO_PATH.is_match(path)
+            && !path.starts_with('/')
+            && !path.split('/').any(|seg| seg.is_empty() || seg == "." || seg == "..");
+        if !path_ok {
+            bail!("invalid repo path: {:?}", path);
+        }
+        if let Some(frag) = lines {
+            if !LINE_RANGE.is_match(frag) {
+                bail!("invalid line range #{} (expected #L10 or #L10-L20)", frag);
+            }
+        }
+        let anchor = lines.map(|l| format!("#{}", l)).unwrap_or_default();
+        let (href, artifact) = if let Some(id) = self.files.get(path) {
+            (format!("{}/a/{}/{}", self.base, id, anchor), Some(id.clone()))
+        } else if self.bundled.contains(path) {
+            (format!("https://github.com/{}/blob/{}/{}{}", self.repo, self.commit, path, anchor), None)
+        } else {
+            bail!("repo path {} is not in the manifest (add a repo:file artifact or a repo:bundle containing it)", path);
+        };
+        let label = label.unwrap_or_else(|| match lines {
+            Some(l) => format!("{} ({})", path, l.replace("-L", "\u{2013}L")),
+            None => path.to_string(),
+        });
+        Ok(Link { href, label, artifact })
+    }
+
+    fn symbol_link(&self, spec: &str) -> Result<Link> {
+        let (path, name) = spec.split_once("::").ok_or_else(|| anyhow!("expected sym:<path>::<name>"))?;
+        let lines: BTreeSet<u64> = self.symbols.iter().filter(|s| s.path == path && s.name == name).map(|s| s.line).collect();
+        let line = match lines.len() {
+            0 => bail!("symbol {} not found in {} (no repo:symbols entry)", name, path),
+            1 => *lines.iter().next().unwrap(),
+            _ => bail!("symbol {} is ambiguous in {} (lines {:?})", name, path, lines),
+        };
+        self.repo_link(&format!("{}#L{}", path, line), Some(name.to_string()))
+    }
+
+    fn github(&self, n: &str, kind: &str, label: String) -> Result<Link> {
+        if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+            bail!("invalid GitHub {} number: {:?}", kind, n);
+        }
+        Ok(Link { href: format!("https://github.com/{}/{}/{}", self.repo, kind, n), label, artifact: None })
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// File paths in a tar or gzip-compressed tar. When every entry shares one top-level directory
+/// (`git archive --prefix=...`), paths are also recorded without it.
+fn bundle_paths(bytes: &[u8]) -> Result<BTreeSet<String>> {
+    let tar = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut out = Vec::new();
+        flate2::read::GzDecoder::new(bytes).read_to_end(&mut out).context("decompress")?;
+        out
+    } else {
+        bytes.to_vec()
+    };
+    let names: BTreeSet<String> = read_tar(&tar)?.into_keys().map(|n| n.trim_start_matches("./").to_string()).collect();
+    let first = |n: &str| n.split_once('/').map(|(d, _)| d.to_string());
+    let prefix = names.iter().next().and_then(|n| first(n));
+    let mut paths = names.clone();
+    if let Some(p) = prefix.filter(|p| names.iter().all(|n| first(n).as_deref() == Some(p.as_str()))) {
+        paths.extend(names.iter().map(|n| n[p.len() + 1..].to_string()));
+    }
+    Ok(paths)
+}
+
+/// A JSON array of `{name, path, line}`, an object with such a `symbols` array, or ctags
+/// `--output-format=json` lines (entries without a line are skipped).
+fn parse_symbols(bytes: &[u8]) -> Result<Vec<Symbol>> {
+    #[derive(Deserialize)]
+    struct Wrapped {
+        symbols: Vec<Symbol>,
+    }
+    if let Ok(list) = serde_json::from_slice::<Vec<Symbol>>(bytes) {
+        return Ok(list);
+    }
+    if let Ok(w) = serde_json::from_slice::<Wrapped>(bytes) {
+        return Ok(w.symbols);
+    }
+    let text = std::str::from_utf8(bytes).context("symbols are not UTF-8")?;
+    let mut out = Vec::new();
+    for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let v: serde_json::Value = serde_json::from_str(line).with_context(|| format!("line {}", i + 1))?;
+        if let Ok(sym) = serde_json::from_value::<Symbol>(v) {
+            out.push(sym);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> LinkIndex {
+        LinkIndex {
+            base: "/proj".into(),
+            repo: "acme/widgets".into(),
+            commit: "deadbeef".into(),
+            run_id: "42".into(),
+            run_url: "https://github.com/acme/widgets/actions/runs/42".into(),
+            artifacts: [("coverage".to_string(), "Coverage".to_string()), ("src-lib-rs".to_string(), "src/lib.rs".to_string())].into(),
+            files: [("src/lib.rs".to_string(), "src-lib-rs".to_string())].into(),
+            bundled: ["src/main.rs".to_string()].into(),
+            symbols: vec![Symbol { name: "init".into(), path: "src/lib.rs".into(), line: 12 }],
+        }
+    }
+
+    fn href(target: &str) -> String {
+        index().resolve(target).unwrap().href
+    }
+
+    #[test]
+    fn resolves_each_scheme() {
+        assert_eq!(index().resolve("a:coverage").unwrap(), Link { href: "/proj/a/coverage/".into(), label: "Coverage".into(), artifact: Some("coverage".into()) });
+        assert_eq!(href("repo:src/lib.rs#L10-L20"), "/proj/a/src-lib-rs/#L10-L20");
+        assert_eq!(href("src/main.rs#L3"), "https://github.com/acme/widgets/blob/deadbeef/src/main.rs#L3");
+        assert_eq!(href("sym:src/lib.rs::init"), "/proj/a/src-lib-rs/#L12");
+        assert_eq!(href("doc:#failures"), "#failures");
+        assert_eq!(href("gh:pr:7"), "https://github.com/acme/widgets/pull/7");
+        assert_eq!(href("ci:run"), "https://github.com/acme/widgets/actions/runs/42");
+        assert_eq!(index().resolve("ci:job:build").unwrap(), Link { href: "https://github.com/acme/widgets/actions/runs/42".into(), label: "build".into(), artifact: None });
+        assert_eq!(index().resolve("repo:src/lib.rs#L10-L20").unwrap().label, "src/lib.rs (L10\u{2013}L20)");
+    }
+
+    #[test]
+    fn unresolvable_targets_are_errors() {
+        for bad in ["a:nope", "repo:src/../etc/passwd", "repo:src/other.rs", "sym:src/lib.rs::missing", "gh:issue:abc", "javascript:alert(1)", "src/lib.rs#10"] {
+            assert!(index().resolve(bad).is_err(), "{} should not resolve", bad);
+        }
+    }
+
+    #[test]
+    fn expands_macros_in_text() {
+        let html = index().expand("See [[a:coverage | the <full> report]] and [[ci:run]].", &Vars::default(), &|_| {}).unwrap();
+        assert_eq!(html, "See <a href=\"/proj/a/coverage/\">the &lt;full&gt; report</a> and <a href=\"https://github.com/acme/widgets/actions/runs/42\">CI run 42</a>.");
+        assert!(index().expand("[[a:coverage", &Vars::default(), &|_| {}).is_err());
+    }
+}
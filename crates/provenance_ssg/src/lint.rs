@@ -0,0 +1,498 @@
+// Proofdown lint: checks a `.pml` file against the component registry, attribute bounds and the
+// verified manifest without building a site, and reports every problem with its line and column.
+//
+// Components, `[[...]]` link macros and `{{ ... }}` placeholders are located by scanning the source
+// (fenced code and inline code spans are skipped), so positions are exact even without the parser.
+// The scanner only finds what the renderer would be handed in `pml_ast` form; a test holds it to
+// the example's `front_page.ast.json`, the export the site renders. With `external_pml`, parse
+// errors from the parser are reported as well.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::interpolate::Vars;
+use crate::links::LinkIndex;
+use crate::proofdown;
+use crate::ssg::{load_verified, Args, ArtifactViewExt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One finding. `code` is stable and meant for tooling; `message` is for people.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Lint result for one file, in the shape printed by `lint-pml --format json`.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub file: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Lint `file` (default: the manifest's `front_page.markup`), relative to `root`.
+pub fn lint_pml(root: &Path, manifest: &Path, schema_path: Option<PathBuf>, file: Option<&Path>) -> Result<Report> {
+    let args = Args {
+        root: root.to_path_buf(),
+        manifest: manifest.to_path_buf(),
+        out: PathBuf::new(),
+        copy_assets: false,
+        schema_path,
+        ..Default::default()
+    };
+    let verified = load_verified(&args)?;
+    let rel = file.map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from(&verified.manifest.front_page.markup));
+    let src = fs::read_to_string(root.join(&rel)).map_err(|e| anyhow::anyhow!("read {}: {}", root.join(&rel).display(), e))?;
+    let links = LinkIndex::build(&verified.manifest, &verified.views, root, "")?;
+    let diagnostics = lint_source(&src, &LintCtx { views: &verified.views, root, vars: &verified.vars, links: &links });
+    let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    Ok(Report { file: rel.to_string_lossy().into_owned(), errors, warnings: diagnostics.len() - errors, diagnostics })
+}
+
+pub(crate) struct LintCtx<'a> {
+    pub views: &'a [ArtifactViewExt],
+    pub root: &'a Path,
+    pub vars: &'a Vars,
+    pub links: &'a LinkIndex,
+}
+
+struct Tag {
+    name: String,
+    at: usize,
+    attrs: Vec<(String, String, usize)>,
+    close: bool,
+    self_close: bool,
+    end: usize,
+}
+
+/// Diagnostics for `src`, ordered by position.
+pub(crate) fn lint_source(src: &str, cx: &LintCtx) -> Vec<Diagnostic> {
+    let mut l = Linter { src, cx, diags: Vec::new() };
+    #[cfg(feature = "external_pml")]
+    if let Err(e) = proofdown_parser::parse(src) {
+        l.diags.push(Diagnostic { line: e.line, column: e.col, severity: Severity::Error, code: "parse", message: e.msg.to_string() });
+    }
+    let mut stack: Vec<(String, usize)> = Vec::new();
+    for token in scan(src) {
+        match token {
+            Token::Malformed(at, msg) => l.error(at, "malformed-tag", msg),
+            Token::Tag(tag) if tag.close => match stack.last() {
+                Some((open, _)) if *open == tag.name => {
+                    stack.pop();
+                }
+                Some((open, _)) => l.error(tag.at, "unexpected-close", format!("</{}> does not close <{}>", tag.name, open)),
+                None => l.error(tag.at, "unexpected-close", format!("</{}> has no matching open tag", tag.name)),
+            },
+            Token::Tag(tag) => {
+                l.component(&tag, stack.last().map(|(n, _)| n.as_str()));
+                if !tag.self_close {
+                    stack.push((tag.name, tag.at));
+                }
+            }
+            Token::Link(at, Some(inner)) => l.link(at, inner),
+            Token::Link(at, None) => l.error(at, "link", "unterminated link macro".to_string()),
+            Token::Placeholder(at, text) => {
+                if let Err(e) = cx.vars.render(text) {
+                    l.error(at, "placeholder", e.to_string());
+                }
+            }
+        }
+    }
+    for (name, at) in stack {
+        l.error(at, "unclosed-component", format!("<{}> is never closed", name));
+    }
+    l.diags.sort_by_key(|d| (d.line, d.column));
+    l.diags
+}
+
+/// What `scan` finds in a source, at byte offsets into it.
+enum Token<'a> {
+    Tag(Tag),
+    /// A `<name` that does not end as a well-formed tag
+    Malformed(usize, String),
+    /// `[[inner]]`; `None` when the macro is never closed
+    Link(usize, Option<&'a str>),
+    /// `{{ ... }}`, up to the end of the source when never closed
+    Placeholder(usize, &'a str),
+}
+
+/// Components, link macros and placeholders outside code, in source order.
+fn scan(src: &str) -> Vec<Token<'_>> {
+    let masked = mask_code(src);
+    let b = masked.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'<' {
+            match scan_tag(&masked, src, i) {
+                None => {}
+                Some(Err((at, msg))) => {
+                    tokens.push(Token::Malformed(at, msg));
+                    i = masked[i..].find('>').map_or(b.len(), |n| i + n + 1);
+                    continue;
+                }
+                Some(Ok(tag)) => {
+                    i = tag.end;
+                    tokens.push(Token::Tag(tag));
+                    continue;
+                }
+            }
+        }
+        if masked[i..].starts_with("[[") {
+            let end = masked[i + 2..].find("]]").map(|n| i + 2 + n);
+            tokens.push(Token::Link(i, end.map(|end| &src[i + 2..end])));
+            i = end.map_or(b.len(), |e| e + 2);
+            continue;
+        }
+        if masked[i..].starts_with("{{") {
+            let end = masked[i + 2..].find("}}").map_or(b.len(), |n| i + 2 + n + 2);
+            tokens.push(Token::Placeholder(i, &src[i..end]));
+            i = end;
+            continue;
+        }
+        // One character at a time, so `i` stays on a UTF-8 boundary
+        i += masked[i..].chars().next().map_or(1, char::len_utf8);
+    }
+    tokens
+}
+
+struct Linter<'a> {
+    src: &'a str,
+    cx: &'a LintCtx<'a>,
+    diags: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn push(&mut self, at: usize, severity: Severity, code: &'static str, message: String) {
+        let before = &self.src[..at];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+        self.diags.push(Diagnostic { line, column, severity, code, message });
+    }
+
+    fn error(&mut self, at: usize, code: &'static str, message: String) {
+        self.push(at, Severity::Error, code, message);
+    }
+
+    fn link(&mut self, at: usize, inner: &str) {
+        let (target, label) = match inner.split_once('|') {
+            Some((t, l)) => (t.trim(), Some(l.trim())),
+            None => (inner.trim(), None),
+        };
+        if let Err(e) = self.cx.links.resolve(target) {
+            self.error(at, "link", format!("[[{}]]: {}", inner.trim(), e));
+        }
+        if let Some(Err(e)) = label.map(|l| self.cx.vars.render(l)) {
+            self.error(at, "placeholder", e.to_string());
+        }
+    }
+
+    fn component(&mut self, tag: &Tag, parent: Option<&str>) {
+        let name = tag.name.as_str();
+        if let Err(e) = proofdown::check_attrs(name, []) {
+            self.error(tag.at, "unknown-component", e.to_string());
+            return;
+        }
+        let mut seen: Vec<&str> = Vec::new();
+        for (key, _, at) in &tag.attrs {
+            if seen.contains(&key.as_str()) {
+                self.error(*at, "duplicate-attribute", format!("<{}> repeats attribute {:?}", name, key));
+            } else if let Err(e) = proofdown::check_attrs(name, [key.as_str()]) {
+                self.error(*at, "unknown-attribute", e.to_string());
+            }
+            seen.push(key);
+        }
+        for (_, value, at) in &tag.attrs {
+            if let Err(e) = self.cx.vars.render(value) {
+                self.error(*at, "placeholder", e.to_string());
+            }
+        }
+
+        if let Err(e) = proofdown::check_placement(Some(name), parent) {
+            self.error(tag.at, "misplaced-component", e.to_string());
+        }
+
+        let attr = |key: &str| tag.attrs.iter().find(|(k, _, _)| k == key).map(|(_, v, _)| v.as_str());
+        let attr_at = |key: &str| tag.attrs.iter().find(|(k, _, _)| k == key).map_or(tag.at, |(_, _, at)| *at);
+        let mut checks: Vec<(&str, Result<()>)> = Vec::new();
+        for (_, key, ..) in proofdown::INT_ATTRS.iter().filter(|(c, ..)| *c == name) {
+            checks.push((key, proofdown::bounded_attr(name, key, attr(key)).map(drop)));
+        }
+        match name {
+            "section" | "tab" => checks.push(("title", proofdown::required_attr(name, "title", attr("title")).map(drop))),
+            "artifact.gauge" => {
+                let min = proofdown::num_attr(name, "min", attr("min"), 0.0);
+                let max = proofdown::num_attr(name, "max", attr("max"), 100.0);
+                if let (Ok(min), Ok(max)) = (&min, &max) {
+                    checks.push(("min", proofdown::gauge_range(*min, *max)));
+                }
+                checks.push(("min", min.map(drop)));
+                checks.push(("max", max.map(drop)));
+                checks.push(("warn", proofdown::Thresholds::from_attrs(name, attr("warn"), attr("ok")).map(drop)));
+            }
+            _ => {}
+        }
+        for (key, res) in checks {
+            if let Err(e) = res {
+                self.error(attr_at(key), "invalid-attribute", e.to_string());
+            }
+        }
+
+        let takes_id = name == "image" || name == "include.pml" || name.starts_with("artifact.");
+        if !takes_id {
+            return;
+        }
+        let id = match proofdown::required_attr(name, "id", attr("id")) {
+            Ok(id) => id,
+            Err(e) => return self.error(tag.at, "invalid-attribute", e.to_string()),
+        };
+        let Some(v) = self.cx.views.iter().find(|v| v.artifact.id == id) else {
+            return self.error(attr_at("id"), "unknown-artifact", format!("<{}> references unknown artifact id: {}", name, id));
+        };
+        let a = &v.artifact;
+        if !v.verified {
+            self.push(attr_at("id"), Severity::Warning, "unverified-artifact", format!("{} failed digest verification", id));
+        }
+        if let Err(e) = proofdown::check_render(name, id, &a.render) {
+            return self.error(attr_at("id"), "incompatible-artifact", e.to_string());
+        }
+        let compatible = match name {
+            "image" | "artifact.image" => proofdown::check_image(name, id, &a.media_type),
+            "artifact.viewer" => proofdown::check_viewer(id, &a.render, attr("kind")),
+            _ => Ok(()),
+        };
+        if let Err(e) = compatible {
+            return self.error(attr_at("id"), "incompatible-artifact", e.to_string());
+        }
+
+        if let Some(kind) = ["artifact.gauge", "artifact.kpi", "artifact.sparkline"].iter().find(|k| **k == name) {
+            let Some(pointer) = attr("pointer") else {
+                return self.error(tag.at, "invalid-attribute", format!("<{}> requires pointer attribute", kind));
+            };
+            let doc = fs::read(self.cx.root.join(&a.path)).ok().and_then(|b| serde_json::from_slice::<serde_json::Value>(&b).ok());
+            let res = match doc {
+                None => Err(anyhow::anyhow!("<{}> {} is not readable JSON", kind, id)),
+                Some(doc) if *kind == "artifact.sparkline" => proofdown::json_series(kind, &doc, pointer).map(drop),
+                Some(doc) => proofdown::json_number(kind, &doc, pointer).map(drop),
+            };
+            if let Err(e) = res {
+                self.error(attr_at("pointer"), "invalid-attribute", e.to_string());
+            }
+        }
+    }
+}
+
+/// Copy of `src` with fenced code blocks and inline code spans blanked (same byte offsets).
+fn mask_code(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let blank = |s: &str| s.bytes().map(|b| if b == b'\n' { '\n' } else { ' ' }).collect::<String>();
+    let mut fence: Option<&str> = None;
+    for line in src.split_inclusive('\n') {
+        let marker = ["```", "~~~"].into_iter().find(|m| line.trim_start().starts_with(m));
+        match (fence, marker) {
+            (None, Some(m)) => {
+                fence = Some(m);
+                out.push_str(&blank(line));
+            }
+            (Some(f), Some(m)) if f == m => {
+                fence = None;
+                out.push_str(&blank(line));
+            }
+            (Some(_), _) => out.push_str(&blank(line)),
+            (None, None) => {
+                let mut rest = line;
+                while let Some(start) = rest.find('`') {
+                    let run = rest[start..].bytes().take_while(|b| *b == b'`').count();
+                    let ticks = &rest[start..start + run];
+                    match rest[start + run..].find(ticks) {
+                        Some(n) => {
+                            let end = start + run + n + run;
+                            out.push_str(&rest[..start]);
+                            out.push_str(&blank(&rest[start..end]));
+                            rest = &rest[end..];
+                        }
+                        None => {
+                            out.push_str(&rest[..start + run]);
+                            rest = &rest[start + run..];
+                        }
+                    }
+                }
+                out.push_str(rest);
+            }
+        }
+    }
+    out
+}
+
+/// Parse the tag starting at `start` (`<name ...>`, `<name ... />` or `</name>`, grammar in
+/// `.specs/10_proofdown.md` §14). `None` when the `<` does not start a tag (e.g. `a < b`).
+fn scan_tag(masked: &str, src: &str, start: usize) -> Option<std::result::Result<Tag, (usize, String)>> {
+    let b = masked.as_bytes();
+    let mut j = start + 1;
+    let close = b.get(j) == Some(&b'/');
+    if close {
+        j += 1;
+    }
+    if !b.get(j).is_some_and(u8::is_ascii_lowercase) {
+        return None;
+    }
+    let name_start = j;
+    while b.get(j).is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == b'.' || *c == b'-') {
+        j += 1;
+    }
+    let mut tag = Tag { name: masked[name_start..j].to_string(), at: start, attrs: Vec::new(), close, self_close: false, end: 0 };
+    loop {
+        while b.get(j).is_some_and(u8::is_ascii_whitespace) {
+            j += 1;
+        }
+        match b.get(j) {
+            None => return Some(Err((start, format!("unterminated tag <{}", tag.name)))),
+            Some(b'>') => {
+                tag.end = j + 1;
+                return Some(Ok(tag));
+            }
+            Some(b'/') if !close && b.get(j + 1) == Some(&b'>') => {
+                tag.self_close = true;
+                tag.end = j + 2;
+                return Some(Ok(tag));
+            }
+            Some(c) if !close && c.is_ascii_lowercase() => {
+                let key_start = j;
+                while b.get(j).is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == b'-' || *c == b'_') {
+                    j += 1;
+                }
+                let key = masked[key_start..j].to_string();
+                if b.get(j) != Some(&b'=') {
+                    return Some(Err((j, format!("<{}> attribute {} needs a value (key=value)", tag.name, key))));
+                }
+                j += 1;
+                let value = if b.get(j) == Some(&b'"') {
+                    let Some(n) = masked[j + 1..].find('"') else {
+                        return Some(Err((j, format!("<{}> attribute {} has an unterminated quoted value", tag.name, key))));
+                    };
+                    let end = j + 1 + n;
+                    let v = src[j + 1..end].to_string();
+                    j = end + 1;
+                    v
+                } else {
+                    let v_start = j;
+                    while b.get(j).is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'/' | b':' | b'.')) {
+                        j += 1;
+                    }
+                    if j == v_start {
+                        return Some(Err((j, format!("<{}> attribute {} has an empty or unquoted value", tag.name, key))));
+                    }
+                    src[v_start..j].to_string()
+                };
+                tag.attrs.push((key, value, key_start));
+            }
+            Some(_) => return Some(Err((j, format!("unexpected character in <{}{}> tag", if close { "/" } else { "" }, tag.name)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pml_ast;
+    use crate::ssg::test_support::*;
+
+    fn lint(src: &str) -> Vec<(usize, usize, &'static str)> {
+        let root = example();
+        let args = Args { copy_assets: false, ..test_args(root.clone(), PathBuf::new()) };
+        let v = load_verified(&args).unwrap();
+        let links = LinkIndex::build(&v.manifest, &v.views, &root, "").unwrap();
+        lint_source(src, &LintCtx { views: &v.views, root: &root, vars: &v.vars, links: &links }).into_iter().map(|d| (d.line, d.column, d.code)).collect()
+    }
+
+    #[test]
+    fn example_front_page_is_clean() {
+        let src = std::fs::read_to_string(example().join("ci/front_page.pml")).unwrap();
+        // Only digest warnings: two of the example's recorded digests are stale
+        assert!(lint(&src).iter().all(|d| d.2 == "unverified-artifact"), "{:?}", lint(&src));
+    }
+
+    #[test]
+    fn scans_what_the_renderer_sees() {
+        // `<name k=v>` for components, then the `[[...]]` and `{{ ... }}` in text, in document order
+        fn describe(tokens: Vec<Token>) -> Vec<String> {
+            tokens
+                .into_iter()
+                .filter_map(|t| match t {
+                    Token::Tag(tag) if tag.close => None,
+                    Token::Tag(tag) => Some(format!("<{}{}>", tag.name, tag.attrs.iter().map(|(k, v, _)| format!(" {}={}", k, v)).collect::<String>())),
+                    Token::Malformed(_, msg) => Some(msg),
+                    Token::Link(_, inner) => Some(format!("[[{}]]", inner.unwrap_or("unterminated"))),
+                    Token::Placeholder(_, text) => Some(text.to_string()),
+                })
+                .collect()
+        }
+        fn from_ast(blocks: &[pml_ast::Block], out: &mut Vec<String>) {
+            for block in blocks {
+                match block {
+                    pml_ast::Block::Component(c) => {
+                        out.push(format!("<{}{}>", c.name, c.attrs.iter().map(|a| format!(" {}={}", a.key, a.value)).collect::<String>()));
+                        from_ast(&c.children, out);
+                    }
+                    pml_ast::Block::Heading { inlines, .. } | pml_ast::Block::Paragraph { inlines } => {
+                        for inline in inlines {
+                            if let pml_ast::Inline::Text { text } = inline {
+                                out.extend(describe(scan(text)));
+                            }
+                        }
+                    }
+                    other => panic!("fixture uses a block this test does not walk: {:?}", other),
+                }
+            }
+        }
+        let src = std::fs::read_to_string(example().join("ci/front_page.pml")).unwrap();
+        let doc = pml_ast::Document::from_json(&std::fs::read(example().join("ci/front_page.ast.json")).unwrap()).unwrap();
+        let mut expected = Vec::new();
+        from_ast(&doc.blocks, &mut expected);
+        assert_eq!(describe(scan(&src)), expected);
+        assert!(expected.contains(&"<grid cols=3 gap=16>".to_string()) && expected.contains(&"[[a:tests-summary | Full Summary]]".to_string()), "{:?}", expected);
+    }
+
+    #[test]
+    fn reports_each_problem_with_its_position() {
+        let src = "# Résumé — {{ comit }}\n\n<grid cols=9 style=x>\n  <card title=\"T\">\n    <artifact.summary id=\"nope\" />\n    [[a:missing]]\n  </grid>\n<tab title=\"x\"></tab>\n";
+        assert_eq!(
+            lint(src),
+            vec![
+                (1, 12, "placeholder"),
+                (3, 1, "unclosed-component"),
+                (3, 7, "invalid-attribute"),
+                (3, 14, "unknown-attribute"),
+                (4, 3, "unclosed-component"),
+                (5, 23, "unknown-artifact"),
+                (6, 5, "link"),
+                (7, 3, "unexpected-close"),
+                (8, 1, "misplaced-component"),
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_components_that_cannot_show_the_artifact() {
+        assert_eq!(lint("<artifact.markdown id=\"coverage\" />\n<artifact.link id=\"coverage\" />\n"), vec![(1, 20, "incompatible-artifact")]);
+    }
+
+    #[test]
+    fn skips_code_and_plain_comparisons() {
+        assert_eq!(lint("Use `<grid cols=99>` like so; 1 < 2.\n\n```\n<marquee>\n```\n"), vec![]);
+        assert_eq!(lint("<grid cols=\"3\"\n  gap=8>\n</grid>\n"), vec![]);
+    }
+}
@@ -0,0 +1,263 @@
+// Proofdown pages: the index (the authored front page, else the built-in overview), manifest
+// `pages` at /p/{id}/ and, with the parser linked, every verified Proofdown artifact. A page is read
+// from its verified `proofdown:ast` export when it names one, else parsed from its markup.
+
+use anyhow::{anyhow, Context, Result};
+use manifest_contract as mc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::interpolate::Vars;
+use crate::links::LinkIndex;
+use crate::pml_ast;
+#[cfg(feature = "external_pml")]
+use crate::pml_render::parse_pml;
+use crate::pml_render::{load_artifact_doc, render_proofdown, Citations, PmlCtx};
+use crate::proofdown;
+use crate::ssg::{ArtifactViewExt, Site};
+use frontend as fe;
+
+/// Write the index and every Proofdown page, recording what they cite in `citations`. Returns
+/// how many pages were rendered from Proofdown (the built-in overview and page sources shown as
+/// text do not count).
+pub(crate) fn publish(
+    site: &Site,
+    manifest: &mc::Manifest,
+    views: &[ArtifactViewExt],
+    vars: &Vars,
+    kpis: &BTreeMap<&'static str, String>,
+    citations: &Citations,
+) -> Result<usize> {
+    let args = site.args;
+    let links = LinkIndex::build(manifest, views, &args.root, &site.base)?;
+    let pml_ctx = |origin: &str| PmlCtx {
+        vars,
+        links: &links,
+        views,
+        truncate_limit: args.truncate_inline_bytes,
+        root: &args.root,
+        base: &site.base,
+        max_include_depth: args.max_include_depth,
+        ids: proofdown::Ids::default(),
+        includes: RefCell::new(vec![origin.to_string()]),
+        cited: citations,
+    };
+
+    // Index page: the authored front page when there is one to render, else the built-in overview
+    let mut rendered = 0;
+    let index_inner = match front_page_doc(manifest, views, &args.root)? {
+        Some(doc) => {
+            rendered += 1;
+            render_proofdown(&doc, &pml_ctx("front page"))?
+        }
+        None => {
+            let featured: Vec<fe::Artifact> = views.iter().map(ArtifactViewExt::summary).collect();
+            fe::render_index(&site.base, &site.title, &manifest.commit, kpis, &featured)
+        }
+    };
+    let description = format!("Verified CI evidence for {}: {} artifacts", site.at_commit, views.len());
+    site.write_page("/", site.title.clone(), description, index_inner)?;
+
+    // Every verified Proofdown artifact (include targets) is also a page of its own
+    #[cfg(feature = "external_pml")]
+    for v in views.iter().filter(|v| v.artifact.render == "proofdown" && v.verified) {
+        let a = &v.artifact;
+        let inner = render_proofdown(&load_artifact_doc(&args.root, v)?, &pml_ctx(&a.id))?;
+        rendered += 1;
+        site.write_page(&format!("/p/{}/", a.id), format!("{} — {}", a.title, site.title), format!("{} from {}", a.title, site.at_commit), inner)?;
+    }
+
+    // Authored pages; a page that cannot be rendered in this build shows its Proofdown source so
+    // navigation links still resolve
+    for (p, page_title) in &site.pages {
+        let inner = match page_doc(p, views, &args.root)? {
+            Some(doc) => {
+                rendered += 1;
+                render_proofdown(&doc, &pml_ctx(&p.id))?
+            }
+            None => {
+                let src = args.root.join(&p.markup);
+                let text = fs::read_to_string(&src).with_context(|| format!("read page {}", src.display()))?;
+                format!("<header><h1>{}</h1></header><pre><code>{}</code></pre>", proofdown::esc(page_title), proofdown::esc(&text))
+            }
+        };
+        site.write_page(&format!("/p/{}/", p.id), format!("{} — {}", page_title, site.title), format!("{} for {}", page_title, site.at_commit), inner)?;
+    }
+    Ok(rendered)
+}
+
+/// The authored front page: the verified `proofdown:ast` artifact named by `front_page.ast`, else
+/// `front_page.markup` parsed when the parser is linked. `None` means the built-in overview.
+fn front_page_doc(manifest: &mc::Manifest, views: &[ArtifactViewExt], root: &Path) -> Result<Option<pml_ast::Document>> {
+    if let Some(id) = manifest.front_page.ast.as_deref() {
+        return ast_doc("front_page.ast", id, views, root).map(Some);
+    }
+    #[cfg(feature = "external_pml")]
+    return parse_pml(root, &manifest.front_page.markup).map(Some);
+    #[cfg(not(feature = "external_pml"))]
+    Ok(None)
+}
+
+/// A manifest page, chosen like `front_page_doc`. `None` when this build cannot render it.
+fn page_doc(page: &mc::Page, views: &[ArtifactViewExt], root: &Path) -> Result<Option<pml_ast::Document>> {
+    if let Some(id) = page.ast.as_deref() {
+        return ast_doc(&format!("page {} ast", page.id), id, views, root).map(Some);
+    }
+    #[cfg(feature = "external_pml")]
+    return parse_pml(root, &page.markup).map(Some);
+    #[cfg(not(feature = "external_pml"))]
+    Ok(None)
+}
+
+/// The verified `proofdown:ast` artifact `id`, named by `what` (e.g. "front_page.ast").
+fn ast_doc(what: &str, id: &str, views: &[ArtifactViewExt], root: &Path) -> Result<pml_ast::Document> {
+    let v = views.iter().find(|v| v.artifact.id == id).ok_or_else(|| anyhow!("{} {} is not an artifact", what, id))?;
+    if !v.verified {
+        return Err(anyhow!("{} {} failed digest verification", what, id));
+    }
+    load_artifact_doc(root, v)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssg::run_with_args;
+    use crate::ssg::test_support::*;
+
+    #[test]
+    fn renders_front_page_from_ast_artifact() {
+        let root = example_copy();
+        set_front_page_ast(&root, &std::fs::read(root.join("ci/front_page.ast.json")).unwrap());
+
+        let out = unique_out();
+        let args = test_args(root.clone(), out.clone());
+        run_with_args(args).expect("site generation succeeds");
+        let index = std::fs::read_to_string(out.join("index.html")).expect("read index");
+        assert!(index.contains("<h1>QA Evidence for deadbeef</h1>"));
+        assert!(index.contains("<div class=\"cards\" style=\"grid-template-columns:repeat(3,minmax(280px,1fr));gap:16px\"><div class=\"card\"><h3>Tests</h3>"));
+        assert!(index.contains("<a href=\"/a/tests-summary/\">Full Summary</a>"));
+
+        // A tampered export is refused rather than rendered
+        std::fs::write(root.join("ci/front_page.ast.json"), b"{\"blocks\": []}").unwrap();
+        let args = test_args(root.clone(), unique_out());
+        let err = run_with_args(args).unwrap_err().to_string();
+        assert!(err.contains("front_page.ast front-page-ast failed digest verification"), "{}", err);
+    }
+
+    #[test]
+    fn proofdown_refuses_unverified_evidence() {
+        let root = example_copy();
+        std::fs::write(root.join("ci/shot.png"), b"\x89PNG\r\n").unwrap();
+        edit_manifest(&root, |m| {
+            m["artifacts"].as_array_mut().unwrap().push(serde_json::json!({
+                "id": "shot", "title": "Screenshot", "path": "ci/shot.png", "media_type": "image/png", "render": "image", "sha256": "0".repeat(64)
+            }));
+            m["artifacts"].as_array_mut().unwrap().push(serde_json::json!({
+                "id": "shot-raw", "title": "Screenshot", "path": "ci/shot.png", "media_type": "image/png", "render": "raw", "sha256": "0".repeat(64)
+            }));
+        });
+        let build = |ast: &[u8]| {
+            set_front_page_ast(&root, ast);
+            run_with_args(test_args(root.clone(), unique_out())).unwrap_err().to_string()
+        };
+
+        let err = build(br#"{"blocks": [{"type": "component", "name": "gallery", "children": [{"type": "component", "name": "image", "attrs": [{"key": "id", "value": "shot"}]}]}]}"#);
+        assert_eq!(err, "<image> shot failed digest verification");
+        let err = build(br#"{"blocks": [{"type": "paragraph", "inlines": [{"type": "image", "src": "./ci/shot.png", "alt": "Screenshot"}]}]}"#);
+        assert_eq!(err, "image ./ci/shot.png failed digest verification");
+        let err = build(br#"{"blocks": [{"type": "component", "name": "artifact.viewer", "attrs": [{"key": "id", "value": "shot-raw"}, {"key": "kind", "value": "raw"}]}]}"#);
+        assert_eq!(err, "<artifact.viewer> shot-raw failed digest verification");
+        // The example's recorded tests-summary digest is stale
+        let err = build(br#"{"blocks": [{"type": "component", "name": "artifact.kpi", "attrs": [{"key": "id", "value": "tests-summary"}, {"key": "pointer", "value": "/passed"}]}]}"#);
+        assert_eq!(err, "<artifact.kpi> tests-summary failed digest verification");
+    }
+
+    #[test]
+    fn publishes_manifest_pages_with_shared_nav() {
+        let root = example_copy();
+        edit_manifest(&root, |manifest| {
+            manifest["pages"] = serde_json::json!([
+                {"id": "spec", "title": "Spec", "markup": "ci/pages/spec.pml", "nav_order": 2},
+                {"id": "release", "title": "Release checklist for {{ short_commit }}", "markup": "ci/pages/release.pml", "nav_order": 1}
+            ]);
+        });
+        std::fs::create_dir_all(root.join("ci/pages")).unwrap();
+        std::fs::write(root.join("ci/pages/spec.pml"), "# Spec\n").unwrap();
+        std::fs::write(root.join("ci/pages/release.pml"), "# Release\n").unwrap();
+
+        let out = unique_out();
+        let args = test_args(root.clone(), out.clone());
+        run_with_args(args).expect("site generation succeeds");
+        let index = std::fs::read_to_string(out.join("index.html")).expect("read index");
+        let release = index.find("<a href=\"/p/release/\">Release checklist for deadbee</a>").expect("release page in nav");
+        let spec = index.find("<a href=\"/p/spec/\">Spec</a>").expect("spec page in nav");
+        assert!(release < spec, "pages follow nav_order");
+        let page = std::fs::read_to_string(out.join("p").join("spec").join("index.html")).expect("read spec page");
+        assert!(page.contains("<a href=\"/p/spec/\" aria-current=\"page\">Spec</a>"));
+    }
+
+    #[cfg(not(feature = "external_pml"))]
+    #[test]
+    fn renders_pages_from_ast_artifacts() {
+        use sha2::{Digest, Sha256};
+        let root = example_copy();
+        let ast = br#"{"blocks": [{"type": "heading", "level": 1, "inlines": [{"type": "text", "text": "Spec for {{ short_commit }}"}]}]}"#;
+        std::fs::create_dir_all(root.join("ci/pages")).unwrap();
+        std::fs::write(root.join("ci/pages/spec.ast.json"), ast).unwrap();
+        std::fs::write(root.join("ci/pages/spec.pml"), "# Spec\n").unwrap();
+        std::fs::write(root.join("ci/pages/release.pml"), "# Release\n").unwrap();
+        edit_manifest(&root, |manifest| {
+            manifest["artifacts"].as_array_mut().unwrap().push(serde_json::json!({
+                "id": "spec-ast", "title": "Spec (AST)", "path": "ci/pages/spec.ast.json",
+                "media_type": "application/json", "render": "proofdown:ast", "sha256": format!("{:x}", Sha256::digest(ast))
+            }));
+            manifest["pages"] = serde_json::json!([
+                {"id": "spec", "title": "Spec", "markup": "ci/pages/spec.pml", "ast": "spec-ast"},
+                {"id": "release", "title": "Release", "markup": "ci/pages/release.pml"}
+            ]);
+        });
+
+        let out = unique_out();
+        run_with_args(test_args(root.clone(), out.clone())).expect("site generation succeeds");
+        let spec = std::fs::read_to_string(out.join("p/spec/index.html")).expect("read spec page");
+        assert!(spec.contains("<h1>Spec for deadbee</h1>"), "{}", spec);
+        assert!(!spec.contains("<pre><code>"));
+        let release = std::fs::read_to_string(out.join("p/release/index.html")).expect("read release page");
+        assert!(release.contains("<pre><code># Release\n</code></pre>"), "pages without an export show their source");
+
+        std::fs::write(root.join("ci/pages/spec.ast.json"), b"{\"blocks\": []}").unwrap();
+        let err = run_with_args(test_args(root.clone(), unique_out())).unwrap_err().to_string();
+        assert_eq!(err, "page spec ast spec-ast failed digest verification");
+    }
+
+    #[cfg(feature = "external_pml")]
+    #[test]
+    fn renders_markdown_blocks_from_parsed_pages() {
+        use sha2::{Digest, Sha256};
+        let root = example_copy();
+        let png = b"\x89PNG\r\n";
+        std::fs::write(root.join("ci/shot.png"), png).unwrap();
+        std::fs::create_dir_all(root.join("ci/pages")).unwrap();
+        std::fs::write(
+            root.join("ci/pages/notes.pml"),
+            "# Notes\n\n- first\n- second\n\n```rust\nlet a = 1 < 2;\n```\n\n| File | Lines |\n|---|---|\n| src/lib.rs | 92 |\n\nSee [the docs](https://example.com/docs) and ![Login](./ci/shot.png)\n",
+        )
+        .unwrap();
+        edit_manifest(&root, |manifest| {
+            manifest["artifacts"].as_array_mut().unwrap().push(serde_json::json!({
+                "id": "shot", "title": "Screenshot", "path": "ci/shot.png", "media_type": "image/png", "render": "image", "sha256": format!("{:x}", Sha256::digest(png))
+            }));
+            manifest["pages"] = serde_json::json!([{"id": "notes", "title": "Notes", "markup": "ci/pages/notes.pml"}]);
+        });
+
+        let out = unique_out();
+        run_with_args(test_args(root.clone(), out.clone())).expect("site generation succeeds");
+        let page = std::fs::read_to_string(out.join("p/notes/index.html")).expect("read notes page");
+        assert!(page.contains("<ul><li>"), "{}", page);
+        assert!(page.contains("<pre><code class=\"language-rust\">let a = 1 &lt; 2;"));
+        assert!(page.contains("<th scope=\"col\">File</th>"));
+        assert!(page.contains("<a href=\"https://example.com/docs\">the docs</a>"));
+        assert!(page.contains("<img src=\"/assets/shot/shot.png\" alt=\"Login\""));
+    }
+}
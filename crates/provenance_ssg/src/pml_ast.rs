@@ -0,0 +1,189 @@
+// Serializable mirror of the Proofdown AST, the form `pml_render` walks in every build. With
+// `external_pml` the parser's `proofdown_ast` is converted into it (`from_parser`); in any build,
+// Proofdown can be shipped pre-parsed as a verified `render: proofdown:ast` artifact holding this
+// JSON:
+//
+//   {"blocks": [
+//     {"type": "heading", "level": 1, "inlines": [{"type": "text", "text": "{{ front_page.title }}"}]},
+//     {"type": "component", "name": "grid", "attrs": [{"key": "cols", "value": "2"}], "children": [...]}
+//   ]}
+//
+// Unknown node types and fields are rejected, as the parser rejects unknown syntax.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Document {
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+#[allow(clippy::enum_variant_names)] // `CodeBlock` as in proofdown_ast
+pub(crate) enum Block {
+    Heading { level: u8, inlines: Vec<Inline> },
+    Paragraph { inlines: Vec<Inline> },
+    Component(Component),
+    CodeBlock {
+        #[serde(default)]
+        lang: Option<String>,
+        text: String,
+    },
+    List { ordered: bool, items: Vec<Vec<Block>> },
+    Quote { children: Vec<Block> },
+    ThematicBreak,
+    Table { header: Vec<Vec<Inline>>, rows: Vec<Vec<Vec<Inline>>> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Component {
+    pub name: String,
+    #[serde(default)]
+    pub attrs: Vec<Attr>,
+    #[serde(default)]
+    pub children: Vec<Block>,
+}
+
+impl Component {
+    /// First value of attribute `key`.
+    pub(crate) fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs.iter().find(|a| a.key == key).map(|a| a.value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Attr {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub(crate) enum Inline {
+    Text { text: String },
+    Emph { children: Vec<Inline> },
+    Strong { children: Vec<Inline> },
+    Strikethrough { children: Vec<Inline> },
+    Code { text: String },
+    SoftBreak,
+    HardBreak,
+    Link {
+        href: String,
+        #[serde(default)]
+        title: Option<String>,
+        children: Vec<Inline>,
+    },
+    Image {
+        src: String,
+        alt: String,
+        #[serde(default)]
+        title: Option<String>,
+    },
+}
+
+impl Document {
+    pub(crate) fn from_json(bytes: &[u8]) -> Result<Document> {
+        serde_json::from_slice(bytes).context("parse Proofdown AST JSON")
+    }
+}
+
+/// Convert parser output, node for node. Component attributes are looked up by their whitelisted
+/// names (the parser rejects any other).
+#[cfg(feature = "external_pml")]
+pub(crate) fn from_parser(doc: &proofdown_ast::Document) -> Result<Document> {
+    use anyhow::anyhow;
+    use proofdown_ast as ast;
+    use proofdown_parser as pml;
+
+    fn blocks(bs: &[ast::Block]) -> Result<Vec<Block>> {
+        bs.iter().map(block).collect()
+    }
+
+    fn cells(cs: &[Vec<ast::Inline>]) -> Result<Vec<Vec<Inline>>> {
+        cs.iter().map(|c| inlines(c)).collect()
+    }
+
+    fn block(b: &ast::Block) -> Result<Block> {
+        Ok(match b {
+            ast::Block::Heading { level, inlines: i } => {
+                let level = level.to_string().parse().map_err(|_| anyhow!("invalid heading level {}", level))?;
+                Block::Heading { level, inlines: inlines(i)? }
+            }
+            ast::Block::Paragraph { inlines: i } => Block::Paragraph { inlines: inlines(i)? },
+            ast::Block::Component(c) => {
+                let allowed = crate::proofdown::COMPONENTS.iter().find(|(name, _)| *name == c.name.as_str()).map(|(_, keys)| *keys).unwrap_or_default();
+                Block::Component(Component {
+                    name: c.name.to_string(),
+                    attrs: allowed
+                        .iter()
+                        .filter_map(|key| pml::find_attr(&c.attrs, key).map(|value| Attr { key: key.to_string(), value: value.to_string() }))
+                        .collect(),
+                    children: blocks(&c.children)?,
+                })
+            }
+            ast::Block::CodeBlock { lang, text } => Block::CodeBlock { lang: lang.as_ref().map(|l| l.to_string()), text: text.to_string() },
+            ast::Block::List { ordered, items } => Block::List { ordered: *ordered, items: items.iter().map(|item| blocks(item)).collect::<Result<_>>()? },
+            ast::Block::Quote { children } => Block::Quote { children: blocks(children)? },
+            ast::Block::ThematicBreak => Block::ThematicBreak,
+            ast::Block::Table { header, rows } => Block::Table { header: cells(header)?, rows: rows.iter().map(|row| cells(row)).collect::<Result<_>>()? },
+        })
+    }
+
+    fn inlines(is: &[ast::Inline]) -> Result<Vec<Inline>> {
+        is.iter()
+            .map(|i| {
+                Ok(match i {
+                    ast::Inline::Text { text } => Inline::Text { text: text.to_string() },
+                    ast::Inline::Emph { children } => Inline::Emph { children: inlines(children)? },
+                    ast::Inline::Strong { children } => Inline::Strong { children: inlines(children)? },
+                    ast::Inline::Strikethrough { children } => Inline::Strikethrough { children: inlines(children)? },
+                    ast::Inline::Code { text } => Inline::Code { text: text.to_string() },
+                    ast::Inline::SoftBreak => Inline::SoftBreak,
+                    ast::Inline::HardBreak => Inline::HardBreak,
+                    ast::Inline::Link { href, title, children } => {
+                        Inline::Link { href: href.to_string(), title: title.as_ref().map(|t| t.to_string()), children: inlines(children)? }
+                    }
+                    ast::Inline::Image { src, alt, title } => Inline::Image { src: src.to_string(), alt: alt.to_string(), title: title.as_ref().map(|t| t.to_string()) },
+                })
+            })
+            .collect()
+    }
+
+    Ok(Document { blocks: blocks(&doc.blocks)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_tagged_ast_json() {
+        let doc = Document::from_json(
+            br#"{"blocks": [
+                {"type": "heading", "level": 2, "inlines": [{"type": "text", "text": "Tests"}, {"type": "soft_break"}]},
+                {"type": "component", "name": "card", "attrs": [{"key": "title", "value": "Coverage"}],
+                 "children": [{"type": "component", "name": "artifact.table", "attrs": [{"key": "id", "value": "coverage"}]}]},
+                {"type": "code_block", "text": "cargo test"},
+                {"type": "thematic_break"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(doc.blocks.len(), 4);
+        let Block::Component(card) = &doc.blocks[1] else { panic!("expected component") };
+        assert_eq!(card.attr("title"), Some("Coverage"));
+        assert_eq!(card.attr("cols"), None);
+        assert!(matches!(&card.children[0], Block::Component(c) if c.name == "artifact.table" && c.children.is_empty()));
+        assert_eq!(doc.blocks[2], Block::CodeBlock { lang: None, text: "cargo test".into() });
+    }
+
+    #[test]
+    fn rejects_unknown_nodes_and_fields() {
+        assert!(Document::from_json(br#"{"blocks": [{"type": "html", "text": "<script>"}]}"#).is_err());
+        assert!(Document::from_json(br#"{"blocks": [{"type": "paragraph", "inlines": [], "style": "x"}]}"#).is_err());
+        assert!(Document::from_json(br#"{"blocks": [], "version": 2}"#).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+// Proofdown AST → HTML. The walk checks structure (which components nest where, which artifacts
+// they may show) and leaves attribute bounds, escaping and markup to `proofdown`; `[[...]]` links
+// and `{{ ... }}` placeholders in text go through `links` and `interpolate`.
+
+use anyhow::{anyhow, Context, Result};
+#[cfg(feature = "external_pml")]
+use proofdown_parser as pml;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::interpolate::Vars;
+use crate::links::LinkIndex;
+use crate::pml_ast;
+use crate::proofdown;
+use crate::render;
+use crate::ssg::{evidence_links, read_for_render, ArtifactViewExt};
+
+/// Everything a Proofdown component may read while one page is rendered. Element ids are numbered
+/// per page; `includes` is the chain of documents being expanded, starting with the page itself.
+pub(crate) struct PmlCtx<'a> {
+    pub vars: &'a Vars,
+    pub links: &'a LinkIndex,
+    pub views: &'a [ArtifactViewExt],
+    pub truncate_limit: usize,
+    pub root: &'a Path,
+    pub base: &'a str,
+    pub max_include_depth: usize,
+    pub ids: proofdown::Ids,
+    pub includes: RefCell<Vec<String>>,
+    pub cited: &'a Citations,
+}
+
+/// Artifacts cited by the Proofdown pages of one build, through components, inline images or
+/// `[[...]]` links.
+#[derive(Debug, Default)]
+pub(crate) struct Citations {
+    cited: RefCell<BTreeSet<String>>,
+}
+
+impl Citations {
+    fn cite(&self, id: &str) {
+        self.cited.borrow_mut().insert(id.to_string());
+    }
+
+    /// Artifacts no page cites, other than `exempt`. Components showing an artifact they cannot
+    /// display already fail the build.
+    pub(crate) fn coverage_issues(&self, views: &[ArtifactViewExt], exempt: &[&str]) -> Vec<String> {
+        let cited = self.cited.borrow();
+        views
+            .iter()
+            .map(|v| &v.artifact)
+            .filter(|a| !exempt.contains(&a.id.as_str()) && !cited.contains(&a.id))
+            .map(|a| format!("artifact {} ({}) is not referenced by any Proofdown page", a.id, a.render))
+            .collect()
+    }
+}
+
+#[cfg(feature = "external_pml")]
+pub(crate) fn parse_pml(root: &Path, rel: &str) -> Result<pml_ast::Document> {
+    let path = root.join(rel);
+    let text = fs::read_to_string(&path).with_context(|| format!("read Proofdown {}", path.display()))?;
+    let doc = pml::parse(&text).map_err(|e| anyhow!("parse Proofdown {} at {}:{}: {}", rel, e.line, e.col, e.msg))?;
+    pml_ast::from_parser(&doc).with_context(|| format!("Proofdown {}", rel))
+}
+
+/// The document held by a `render: proofdown` (source, needs the parser) or `render: proofdown:ast`
+/// (pre-parsed JSON) artifact.
+pub(crate) fn load_artifact_doc(root: &Path, v: &ArtifactViewExt) -> Result<pml_ast::Document> {
+    let a = &v.artifact;
+    match a.render.as_str() {
+        "proofdown:ast" => {
+            let path = root.join(&a.path);
+            let bytes = fs::read(&path).with_context(|| format!("read Proofdown AST {}", path.display()))?;
+            pml_ast::Document::from_json(&bytes).with_context(|| format!("artifact {}", a.id))
+        }
+        #[cfg(feature = "external_pml")]
+        "proofdown" => parse_pml(root, &a.path),
+        #[cfg(not(feature = "external_pml"))]
+        "proofdown" => Err(anyhow!("{} is Proofdown source; this build has no parser (enable external_pml or ship a proofdown:ast export)", a.id)),
+        other => Err(anyhow!("{} has render {}, expected proofdown or proofdown:ast", a.id, other)),
+    }
+}
+
+pub(crate) fn render_proofdown(doc: &pml_ast::Document, cx: &PmlCtx) -> Result<String> {
+    use crate::proofdown::{Figure, Tab};
+    fn render_blocks(blocks: &[pml_ast::Block], cx: &PmlCtx) -> Result<String> {
+        use pml_ast::Block as B;
+        let mut out = String::new();
+        for b in blocks {
+            match b {
+                B::Heading { level, inlines } => out.push_str(&proofdown::heading(*level, &render_inlines(inlines, cx)?)),
+                B::Paragraph { inlines } => out.push_str(&format!("<p>{}</p>", render_inlines(inlines, cx)?)),
+                B::List { ordered, items } => {
+                    let items = items.iter().map(|item| render_blocks(item, cx)).collect::<Result<Vec<_>>>()?;
+                    out.push_str(&proofdown::list(*ordered, &items));
+                }
+                B::CodeBlock { lang, text } => out.push_str(&proofdown::code_block(lang.as_deref(), text)),
+                B::Table { header, rows } => {
+                    let header = header.iter().map(|cell| render_inlines(cell, cx)).collect::<Result<Vec<_>>>()?;
+                    let rows = rows
+                        .iter()
+                        .map(|row| row.iter().map(|cell| render_inlines(cell, cx)).collect::<Result<Vec<_>>>())
+                        .collect::<Result<Vec<_>>>()?;
+                    out.push_str(&proofdown::table(&header, &rows));
+                }
+                B::Quote { children } => out.push_str(&format!("<blockquote>{}</blockquote>", render_blocks(children, cx)?)),
+                B::ThematicBreak => out.push_str("<hr>"),
+                B::Component(c) => out.push_str(&render_component(c, cx)?),
+            }
+        }
+        Ok(out)
+    }
+
+    // Text is interpolated then escaped; link targets must be safe and images must be verified artifacts.
+    // Adjacent text nodes are joined first since Markdown parsers split `[[...]]` at the brackets.
+    fn render_inlines(inlines: &[pml_ast::Inline], cx: &PmlCtx) -> Result<String> {
+        use pml_ast::Inline as I;
+        let mut out = String::new();
+        let mut text = String::new();
+        for i in inlines {
+            if let I::Text { text: t } = i {
+                text.push_str(t);
+                continue;
+            }
+            if !text.is_empty() {
+                out.push_str(&cx.links.expand(&text, cx.vars, &|id| cx.cited.cite(id))?);
+                text.clear();
+            }
+            match i {
+                I::Text { .. } => unreachable!("text is buffered above"),
+                I::Emph { children } => out.push_str(&format!("<em>{}</em>", render_inlines(children, cx)?)),
+                I::Strong { children } => out.push_str(&format!("<strong>{}</strong>", render_inlines(children, cx)?)),
+                I::Strikethrough { children } => out.push_str(&format!("<del>{}</del>", render_inlines(children, cx)?)),
+                I::Code { text } => out.push_str(&format!("<code>{}</code>", proofdown::esc(text))),
+                I::SoftBreak => out.push('\n'),
+                I::HardBreak => out.push_str("<br>"),
+                I::Link { href, children, .. } => {
+                    out.push_str(&format!("<a href=\"{}\">{}</a>", proofdown::safe_href(href)?, render_inlines(children, cx)?));
+                }
+                I::Image { src, alt, .. } => {
+                    let path = src.trim_start_matches("./");
+                    let v = cx
+                        .views
+                        .iter()
+                        .find(|v| v.artifact.path == path || v.artifact.id == path)
+                        .ok_or_else(|| anyhow!("image {} is not a verified artifact (use its manifest path or id)", src))?;
+                    cx.cited.cite(&v.artifact.id);
+                    if !v.verified {
+                        return Err(anyhow!("image {} failed digest verification", src));
+                    }
+                    out.push_str(&proofdown::image(&v.download_href, alt));
+                }
+            }
+        }
+        out.push_str(&cx.links.expand(&text, cx.vars, &|id| cx.cited.cite(id))?);
+        Ok(out)
+    }
+
+    fn render_component(c: &pml_ast::Component, cx: &PmlCtx) -> Result<String> {
+        let attr = |name: &str| c.attr(name);
+        proofdown::check_attrs(&c.name, c.attrs.iter().map(|a| a.key.as_str()))?;
+        // `<tabs>` and `<gallery>` place their own children; everything here is outside them
+        proofdown::check_placement(Some(&c.name), None)?;
+        match c.name.as_str() {
+            "grid" => {
+                let cols = proofdown::bounded_attr("grid", "cols", attr("cols"))?;
+                let gap = proofdown::bounded_attr("grid", "gap", attr("gap"))?;
+                Ok(proofdown::grid(cols, gap, &render_blocks(&c.children, cx)?))
+            }
+            "card" => {
+                let title = cx.vars.render(attr("title").unwrap_or(""))?;
+                Ok(proofdown::card(&title, &render_blocks(&c.children, cx)?))
+            }
+            "section" => {
+                let title = cx.vars.render(proofdown::required_attr("section", "title", attr("title"))?)?;
+                let id = cx.ids.next("section");
+                Ok(proofdown::section(&id, &title, &render_blocks(&c.children, cx)?))
+            }
+            "tabs" => {
+                let id = cx.ids.next("tabs");
+                let mut tabs = Vec::new();
+                for child in &c.children {
+                    match child {
+                        pml_ast::Block::Component(t) => {
+                            proofdown::check_placement(Some(&t.name), Some("tabs"))?;
+                            proofdown::check_attrs("tab", t.attrs.iter().map(|a| a.key.as_str()))?;
+                            let title = cx.vars.render(proofdown::required_attr("tab", "title", t.attr("title"))?)?;
+                            tabs.push(Tab { title, body: render_blocks(&t.children, cx)? });
+                        }
+                        _ => proofdown::check_placement(None, Some("tabs"))?,
+                    }
+                }
+                proofdown::tabs(&id, &tabs)
+            }
+            "include.pml" => {
+                let id = proofdown::required_attr("include.pml", "id", attr("id"))?;
+                let v = find_view(cx, id)?;
+                cx.cited.cite(id);
+                proofdown::check_render("include.pml", id, &v.artifact.render)?;
+                if !v.verified {
+                    return Err(anyhow!("<include.pml> {} failed digest verification", id));
+                }
+                proofdown::check_include(&cx.includes.borrow(), id, cx.max_include_depth)?;
+                let doc = load_artifact_doc(cx.root, v)?;
+                cx.includes.borrow_mut().push(id.to_string());
+                let html = render_blocks(&doc.blocks, cx);
+                cx.includes.borrow_mut().pop();
+                html
+            }
+            "gallery" => {
+                let cols = proofdown::bounded_attr("gallery", "cols", attr("cols"))?;
+                let mut figures = Vec::new();
+                for child in &c.children {
+                    match child {
+                        pml_ast::Block::Component(img) => {
+                            proofdown::check_placement(Some(&img.name), Some("gallery"))?;
+                            proofdown::check_attrs(&img.name, img.attrs.iter().map(|a| a.key.as_str()))?;
+                            let id = proofdown::required_attr(&img.name, "id", img.attr("id"))?;
+                            let v = find_view(cx, id)?;
+                            cx.cited.cite(id);
+                            proofdown::check_render(&img.name, id, &v.artifact.render)?;
+                            if !v.verified {
+                                return Err(anyhow!("<{}> {} failed digest verification", img.name, id));
+                            }
+                            proofdown::check_image(&img.name, id, &v.artifact.media_type)?;
+                            let alt = match img.attr("alt") {
+                                Some(alt) => cx.vars.render(alt)?,
+                                None => v.artifact.title.clone(),
+                            };
+                            figures.push(Figure {
+                                href: format!("{}/a/{}/", cx.base, v.artifact.id),
+                                src: v.download_href.clone(),
+                                alt,
+                                caption: v.artifact.title.clone(),
+                            });
+                        }
+                        _ => proofdown::check_placement(None, Some("gallery"))?,
+                    }
+                }
+                proofdown::gallery(cols, &figures)
+            }
+            n if n.starts_with("artifact.") => render_artifact_component(&n[9..], c, cx),
+            other => Err(anyhow!("unknown component: {}", other)),
+        }
+    }
+
+    fn find_view<'v>(cx: &PmlCtx<'v>, id: &str) -> Result<&'v ArtifactViewExt> {
+        cx.views.iter().find(|v| v.artifact.id == id).ok_or_else(|| anyhow!("unknown artifact id: {}", id))
+    }
+
+    fn render_artifact_component(kind: &str, c: &pml_ast::Component, cx: &PmlCtx) -> Result<String> {
+        let id = c.attr("id").ok_or_else(|| anyhow!("artifact.* requires id attribute"))?;
+        let v = find_view(cx, id)?;
+        cx.cited.cite(id);
+        let a = &v.artifact;
+        proofdown::check_render(&format!("artifact.{}", kind), id, &a.render)?;
+        let src = cx.root.join(&a.path);
+        // Components that show the artifact through a registered renderer
+        let shown_as = match kind {
+            "summary" => Some("summary:test"),
+            "table" => Some("table:coverage"),
+            "json" => Some("json"),
+            "markdown" => Some("markdown"),
+            "image" => {
+                proofdown::check_image("artifact.image", id, &a.media_type)?;
+                Some("image")
+            }
+            "viewer" => {
+                proofdown::check_viewer(id, &a.render, c.attr("kind"))?;
+                if !v.verified {
+                    return Err(anyhow!("<artifact.viewer> {} failed digest verification", a.id));
+                }
+                Some(a.render.as_str())
+            }
+            _ => None,
+        };
+        if let Some(hint) = shown_as {
+            let r = render::lookup(hint).ok_or_else(|| anyhow!("no renderer for {}", hint))?;
+            let Some((bytes, size)) = read_for_render(r, &src, cx.truncate_limit) else {
+                return Ok(trunc(&v.download_href));
+            };
+            let evidence = evidence_links(cx.views, |id| format!("{}/a/{}/", cx.base, id));
+            let input = render::Input {
+                id: &a.id,
+                title: &a.title,
+                href: &v.download_href,
+                media_type: &a.media_type,
+                sha256: &a.sha256,
+                verified: v.verified,
+                bytes: &bytes,
+                size,
+                evidence: &evidence,
+            };
+            return r.render(&input).map_err(|e| anyhow!("{}: {}", a.id, e));
+        }
+        match kind {
+            "gauge" | "kpi" | "sparkline" => {
+                let component = format!("artifact.{}", kind);
+                if !v.verified {
+                    return Err(anyhow!("<{}> {} failed digest verification", component, a.id));
+                }
+                let attr = |name: &str| c.attr(name);
+                let pointer = attr("pointer").ok_or_else(|| anyhow!("<{}> requires pointer attribute", component))?;
+                let bytes = fs::read(&src).unwrap_or_default();
+                let doc: serde_json::Value = serde_json::from_slice(&bytes).map_err(|e| anyhow!("<{}> {} is not JSON: {}", component, a.id, e))?;
+                let label = attr("label").unwrap_or(&a.title);
+                match kind {
+                    "gauge" => {
+                        let value = proofdown::json_number(&component, &doc, pointer)?;
+                        let min = proofdown::num_attr(&component, "min", attr("min"), 0.0)?;
+                        let max = proofdown::num_attr(&component, "max", attr("max"), 100.0)?;
+                        let thresholds = proofdown::Thresholds::from_attrs(&component, attr("warn"), attr("ok"))?;
+                        proofdown::gauge(label, value, min, max, thresholds)
+                    }
+                    "kpi" => Ok(proofdown::kpi(label, proofdown::json_number(&component, &doc, pointer)?, attr("unit"))),
+                    _ => {
+                        let values = proofdown::json_series(&component, &doc, pointer)?;
+                        let width = proofdown::bounded_attr(&component, "width", attr("width"))?;
+                        let height = proofdown::bounded_attr(&component, "height", attr("height"))?;
+                        Ok(proofdown::sparkline(label, &values, width, height))
+                    }
+                }
+            }
+            "link" => Ok(format!("<a href=\"{}/a/{}/\">{}</a>", proofdown::esc(cx.base), proofdown::esc(&a.id), proofdown::esc(&a.title))),
+            other => Err(anyhow!("unknown artifact component: {}", other)),
+        }
+    }
+
+    fn trunc(href: &str) -> String { format!("<div class=\"card\"><strong>Truncated</strong>: file too large. <a href=\"{}\">Download</a></div>", href) }
+
+    render_blocks(&doc.blocks, cx)
+}
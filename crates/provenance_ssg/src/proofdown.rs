@@ -0,0 +1,655 @@
+// Proofdown markup: Markdown blocks/inlines and structural components. The AST walk in
+// `pml_render` checks attributes and builds markup through these helpers, so bounds, escaping and
+// HTML shape are tested on their own. Bounds follow `.specs/10_proofdown.md` §16.
+//
+//   <grid cols=1..6 gap=0..64>   <section title="...">   <card title="...">
+//   <tabs> <tab title="..."> ... </tab> ... </tabs>
+//   <gallery cols=2..6> <image id="..." alt="..." /> ... </gallery>
+//   <include.pml id="..." />     a verified `render: proofdown` artifact, nested up to --max-include-depth
+//   <artifact.gauge id pointer min max warn ok>   <artifact.kpi id pointer label unit>
+//   <artifact.sparkline id pointer width=40..600 height=12..200>
+//
+// `pointer` is a JSON pointer (RFC 6901) into a JSON artifact. Gauges and sparklines are static
+// SVG with coordinates rounded to two decimals, so the same artifact always renders the same bytes.
+//
+// Tabs are radio inputs + labels toggled by CSS (`.pml-tabs` in `render.rs`): no script, arrow
+// keys move between tabs, and every panel shows when the stylesheet is missing.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+use std::cell::Cell;
+use std::ops::RangeInclusive;
+
+use crate::render;
+
+pub(crate) const GRID_COLS: RangeInclusive<u32> = 1..=6;
+pub(crate) const GRID_GAP: RangeInclusive<u32> = 0..=64;
+pub(crate) const GALLERY_COLS: RangeInclusive<u32> = 2..=6;
+pub(crate) const SPARK_WIDTH: RangeInclusive<u32> = 40..=600;
+pub(crate) const SPARK_HEIGHT: RangeInclusive<u32> = 12..=200;
+/// Most points a sparkline series may have
+pub(crate) const SPARK_MAX_POINTS: usize = 500;
+/// Integer attributes: component, attribute, bounds and default.
+pub(crate) const INT_ATTRS: &[(&str, &str, RangeInclusive<u32>, u32)] = &[
+    ("grid", "cols", GRID_COLS, 3),
+    ("grid", "gap", GRID_GAP, 16),
+    ("gallery", "cols", GALLERY_COLS, 3),
+    ("artifact.sparkline", "width", SPARK_WIDTH, 120),
+    ("artifact.sparkline", "height", SPARK_HEIGHT, 24),
+];
+/// Every component and the attributes it accepts; anything else is rejected (`.specs/10_proofdown.md` §7).
+pub(crate) const COMPONENTS: &[(&str, &[&str])] = &[
+    ("grid", &["cols", "gap"]),
+    ("card", &["title"]),
+    ("section", &["title"]),
+    ("tabs", &[]),
+    ("tab", &["title"]),
+    ("gallery", &["cols"]),
+    ("image", &["id", "alt"]),
+    ("include.pml", &["id"]),
+    ("artifact.summary", &["id"]),
+    ("artifact.table", &["id"]),
+    ("artifact.json", &["id"]),
+    ("artifact.markdown", &["id"]),
+    ("artifact.image", &["id", "alt"]),
+    ("artifact.link", &["id"]),
+    ("artifact.viewer", &["id", "kind"]),
+    ("artifact.gauge", &["id", "pointer", "label", "min", "max", "warn", "ok"]),
+    ("artifact.kpi", &["id", "pointer", "label", "unit"]),
+    ("artifact.sparkline", &["id", "pointer", "label", "width", "height"]),
+];
+
+/// Check a component name and its attribute keys against `COMPONENTS`.
+pub(crate) fn check_attrs<'k>(component: &str, keys: impl IntoIterator<Item = &'k str>) -> Result<()> {
+    let Some((_, allowed)) = COMPONENTS.iter().find(|(name, _)| *name == component) else {
+        bail!("unknown component: {}", component);
+    };
+    for key in keys {
+        if !allowed.contains(&key) {
+            bail!("<{}> does not accept attribute {:?} (allowed: {})", component, key, allowed.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Where a component may appear: `<tab>` only directly inside `<tabs>`, `<image>` only inside
+/// `<gallery>`, and those two hold nothing else. `component` is `None` for Markdown content;
+/// `parent` is the enclosing component.
+pub(crate) fn check_placement(component: Option<&str>, parent: Option<&str>) -> Result<()> {
+    match (component, parent) {
+        (Some("tab"), p) if p != Some("tabs") => bail!("<tab> is only allowed directly inside <tabs>"),
+        (Some("image"), p) if p != Some("gallery") => bail!("<image> is only allowed inside <gallery>"),
+        (c, Some("tabs")) if c != Some("tab") => bail!("<tabs> may only contain <tab> components"),
+        (c, Some("gallery")) if c != Some("image") && c != Some("artifact.image") => bail!("<gallery> may only contain <image> components"),
+        _ => Ok(()),
+    }
+}
+
+/// `kind` values accepted by `<artifact.viewer>`: registered renderers that declare themselves
+/// viewers. Each must equal the artifact's render hint.
+pub(crate) fn viewer_kinds() -> Vec<&'static str> {
+    render::REGISTRY.iter().filter(|r| r.viewer()).map(|r| r.hint()).collect()
+}
+
+/// Fails unless `<artifact.viewer kind=...>` names a viewer that shows artifact `id` (`render`).
+pub(crate) fn check_viewer(id: &str, render: &str, kind: Option<&str>) -> Result<()> {
+    let kind = kind.ok_or_else(|| anyhow!("artifact.viewer requires kind attribute"))?;
+    let kinds = viewer_kinds();
+    if !kinds.contains(&kind) {
+        bail!("artifact.viewer kind must be one of {}; got {:?}", kinds.join(", "), kind);
+    }
+    if render != kind {
+        bail!("artifact.viewer kind={} cannot show {} (render: {})", kind, id, render);
+    }
+    Ok(())
+}
+
+/// Render hints holding JSON, which `artifact.json` and the pointer-based components can read.
+pub(crate) const JSON_RENDERS: &[&str] = &["json", "summary:test", "table:coverage", "llm-proof", "proofdown:ast"];
+
+/// Render hints each artifact-showing component can display, checked before the artifact is read.
+/// `<artifact.viewer>` takes the registered viewers. Components not listed (`artifact.link`,
+/// `[[...]]` links) may point at any artifact.
+pub(crate) const COMPONENT_RENDERS: &[(&str, &[&str])] = &[
+    ("artifact.summary", &["summary:test"]),
+    ("artifact.table", &["table:coverage"]),
+    ("artifact.json", JSON_RENDERS),
+    ("artifact.markdown", &["markdown"]),
+    ("artifact.image", &["image"]),
+    ("image", &["image"]),
+    ("artifact.gauge", JSON_RENDERS),
+    ("artifact.kpi", JSON_RENDERS),
+    ("artifact.sparkline", JSON_RENDERS),
+    ("include.pml", &["proofdown", "proofdown:ast"]),
+];
+
+/// `None` when `component` accepts any render hint.
+pub(crate) fn compatible_renders(component: &str) -> Option<Vec<&'static str>> {
+    if component == "artifact.viewer" {
+        return Some(viewer_kinds());
+    }
+    COMPONENT_RENDERS.iter().find(|(c, _)| *c == component).map(|(_, r)| r.to_vec())
+}
+
+/// Fails unless `component` can display an artifact with render hint `render`.
+pub(crate) fn check_render(component: &str, id: &str, render: &str) -> Result<()> {
+    match compatible_renders(component) {
+        Some(expected) if !expected.contains(&render) => {
+            bail!("<{}> cannot show {} (render: {}); expected {}", component, id, render, expected.join(" or "))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fails unless an image component's artifact has an `image/*` media type.
+pub(crate) fn check_image(component: &str, id: &str, media_type: &str) -> Result<()> {
+    if !render::is_image(media_type) {
+        bail!("<{}> {} has media type {}, expected image/*", component, id, media_type);
+    }
+    Ok(())
+}
+
+/// Integer attribute `name` of `component` with its `INT_ATTRS` bounds and default.
+pub(crate) fn bounded_attr(component: &str, name: &str, raw: Option<&str>) -> Result<u32> {
+    let Some((_, _, range, default)) = INT_ATTRS.iter().find(|(c, n, ..)| *c == component && *n == name) else {
+        bail!("<{}> has no integer attribute {}", component, name);
+    };
+    int_attr(component, name, raw, range.clone(), *default)
+}
+
+/// Integer attribute: decimal digits only, within `range`; `default` when absent.
+pub(crate) fn int_attr(component: &str, name: &str, raw: Option<&str>, range: RangeInclusive<u32>, default: u32) -> Result<u32> {
+    let Some(raw) = raw else { return Ok(default) };
+    let value = match raw.parse::<u32>() {
+        Ok(v) if raw.bytes().all(|b| b.is_ascii_digit()) => v,
+        _ => bail!("<{}> {}={:?} is not an integer", component, name, raw),
+    };
+    if !range.contains(&value) {
+        bail!("<{}> {}={} is out of range {}..={}", component, name, value, range.start(), range.end());
+    }
+    Ok(value)
+}
+
+/// Number attribute: optional `-`, digits, optional `.digits`; `default` when absent.
+pub(crate) fn num_attr(component: &str, name: &str, raw: Option<&str>, default: f64) -> Result<f64> {
+    let Some(raw) = raw else { return Ok(default) };
+    let digits = raw.strip_prefix('-').unwrap_or(raw);
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, "0"));
+    let well_formed = [int, frac].iter().all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()));
+    match raw.parse::<f64>() {
+        Ok(v) if well_formed && v.is_finite() => Ok(v),
+        _ => bail!("<{}> {}={:?} is not a number", component, name, raw),
+    }
+}
+
+/// Required string attribute; empty values count as missing.
+pub(crate) fn required_attr<'a>(component: &str, name: &str, raw: Option<&'a str>) -> Result<&'a str> {
+    match raw.map(str::trim) {
+        Some(v) if !v.is_empty() => Ok(v),
+        _ => bail!("<{}> requires a non-empty {} attribute", component, name),
+    }
+}
+
+/// Element ids for one document: `pml-{prefix}-{n}`, numbered in document order so output is deterministic.
+#[derive(Debug, Default)]
+pub(crate) struct Ids {
+    next: Cell<usize>,
+}
+
+impl Ids {
+    pub(crate) fn next(&self, prefix: &str) -> String {
+        let n = self.next.get() + 1;
+        self.next.set(n);
+        format!("pml-{}-{}", prefix, n)
+    }
+}
+
+pub(crate) struct Tab {
+    pub title: String,
+    /// Rendered panel body (trusted HTML)
+    pub body: String,
+}
+
+pub(crate) struct Figure {
+    /// Link to the artifact page
+    pub href: String,
+    /// Verified image bytes
+    pub src: String,
+    pub alt: String,
+    pub caption: String,
+}
+
+pub(crate) fn grid(cols: u32, gap: u32, inner: &str) -> String {
+    format!(
+        "<div class=\"cards\" style=\"grid-template-columns:repeat({},minmax(280px,1fr));gap:{}px\">{}</div>",
+        cols, gap, inner
+    )
+}
+
+pub(crate) fn card(title: &str, inner: &str) -> String {
+    format!("<div class=\"card\"><h3>{}</h3>{}</div>", esc(title), inner)
+}
+
+pub(crate) fn section(id: &str, title: &str, inner: &str) -> String {
+    format!(
+        "<section class=\"pml-section\" aria-labelledby=\"{id}\"><h2 id=\"{id}\">{}</h2>{}</section>",
+        esc(title),
+        inner,
+        id = id
+    )
+}
+
+/// `id` names the radio group; the first tab is selected.
+pub(crate) fn tabs(id: &str, tabs: &[Tab]) -> Result<String> {
+    if tabs.is_empty() {
+        bail!("<tabs> needs at least one <tab>");
+    }
+    let mut out = format!("<div class=\"pml-tabs\" id=\"{}\">", id);
+    for (i, t) in tabs.iter().enumerate() {
+        let tab_id = format!("{}-tab-{}", id, i + 1);
+        out.push_str(&format!(
+            "<input type=\"radio\" name=\"{id}\" id=\"{tab}\"{checked}><label for=\"{tab}\" id=\"{tab}-label\">{title}</label>\
+             <div class=\"pml-tab-panel\" role=\"region\" aria-labelledby=\"{tab}-label\">{body}</div>",
+            id = id,
+            tab = tab_id,
+            checked = if i == 0 { " checked" } else { "" },
+            title = esc(&t.title),
+            body = t.body
+        ));
+    }
+    out.push_str("</div>");
+    Ok(out)
+}
+
+pub(crate) fn gallery(cols: u32, figures: &[Figure]) -> Result<String> {
+    if figures.is_empty() {
+        bail!("<gallery> needs at least one <image>");
+    }
+    let mut out = format!("<div class=\"pml-gallery cols-{}\">", cols);
+    for f in figures {
+        out.push_str(&format!(
+            "<figure><a href=\"{}\"><img src=\"{}\" alt=\"{}\" loading=\"lazy\"></a><figcaption>{}</figcaption></figure>",
+            esc(&f.href),
+            esc(&f.src),
+            esc(&f.alt),
+            esc(&f.caption)
+        ));
+    }
+    out.push_str("</div>");
+    Ok(out)
+}
+
+/// Gauge coloring: values at or past `ok` are ok, at or past `warn` a warning, otherwise an error.
+/// When `ok` is below `warn`, lower values are better (e.g. failure counts).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Thresholds {
+    pub warn: f64,
+    pub ok: f64,
+}
+
+impl Thresholds {
+    /// Both `warn` and `ok` or neither.
+    pub(crate) fn from_attrs(component: &str, warn: Option<&str>, ok: Option<&str>) -> Result<Option<Thresholds>> {
+        match (warn, ok) {
+            (None, None) => Ok(None),
+            (Some(_), Some(_)) => Ok(Some(Thresholds { warn: num_attr(component, "warn", warn, 0.0)?, ok: num_attr(component, "ok", ok, 0.0)? })),
+            _ => bail!("<{}> needs both warn and ok thresholds, or neither", component),
+        }
+    }
+
+    pub(crate) fn class(&self, value: f64) -> &'static str {
+        let reached = |t: f64| if self.ok >= self.warn { value >= t } else { value <= t };
+        if reached(self.ok) {
+            "ok"
+        } else if reached(self.warn) {
+            "warn"
+        } else {
+            "err"
+        }
+    }
+}
+
+/// The value at `pointer` in a JSON artifact; `""` is the whole document.
+pub(crate) fn json_at<'a>(component: &str, doc: &'a Value, pointer: &str) -> Result<&'a Value> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        bail!("<{}> pointer={:?} must be empty or start with '/'", component, pointer);
+    }
+    doc.pointer(pointer).ok_or_else(|| anyhow!("<{}> pointer {:?} does not resolve", component, pointer))
+}
+
+pub(crate) fn json_number(component: &str, doc: &Value, pointer: &str) -> Result<f64> {
+    json_at(component, doc, pointer)?.as_f64().ok_or_else(|| anyhow!("<{}> pointer {:?} is not a number", component, pointer))
+}
+
+/// An array of numbers, at least two and at most `SPARK_MAX_POINTS`.
+pub(crate) fn json_series(component: &str, doc: &Value, pointer: &str) -> Result<Vec<f64>> {
+    let Some(items) = json_at(component, doc, pointer)?.as_array() else {
+        bail!("<{}> pointer {:?} is not an array", component, pointer);
+    };
+    if !(2..=SPARK_MAX_POINTS).contains(&items.len()) {
+        bail!("<{}> series has {} points; expected 2..={}", component, items.len(), SPARK_MAX_POINTS);
+    }
+    items.iter().map(|v| v.as_f64().ok_or_else(|| anyhow!("<{}> series at {:?} holds a non-number: {}", component, pointer, v))).collect()
+}
+
+/// Numbers as shown to readers: at most two decimals, trailing zeros dropped.
+pub(crate) fn fmt_num(v: f64) -> String {
+    let s = format!("{:.2}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" { "0".to_string() } else { s.to_string() }
+}
+
+/// Fails unless a gauge's `min` is below its `max`.
+pub(crate) fn gauge_range(min: f64, max: f64) -> Result<()> {
+    if min >= max {
+        bail!("<artifact.gauge> min={} must be below max={}", fmt_num(min), fmt_num(max));
+    }
+    Ok(())
+}
+
+/// Half-circle gauge from `min` (left) to `max` (right); the value is clamped to the range.
+pub(crate) fn gauge(label: &str, value: f64, min: f64, max: f64, thresholds: Option<Thresholds>) -> Result<String> {
+    gauge_range(min, max)?;
+    let frac = ((value - min) / (max - min)).clamp(0.0, 1.0);
+    let class = thresholds.map(|t| t.class(value)).unwrap_or("neutral");
+    let theta = std::f64::consts::PI * (1.0 - frac);
+    let (x, y) = (60.0 + 50.0 * theta.cos(), 60.0 - 50.0 * theta.sin());
+    let arc = if frac > 0.0 {
+        format!("<path class=\"arc {}\" d=\"M10 60 A50 50 0 0 1 {} {}\"/>", class, fmt_num(x), fmt_num(y))
+    } else {
+        String::new()
+    };
+    Ok(format!(
+        "<figure class=\"pml-gauge\"><svg viewBox=\"0 0 120 70\" role=\"img\" aria-label=\"{label}: {value} (range {min} to {max})\">\
+         <path class=\"track\" d=\"M10 60 A50 50 0 0 1 110 60\"/>{arc}\
+         <text x=\"60\" y=\"56\" text-anchor=\"middle\">{value}</text></svg><figcaption>{label}</figcaption></figure>",
+        label = esc(label),
+        value = fmt_num(value),
+        min = fmt_num(min),
+        max = fmt_num(max),
+        arc = arc
+    ))
+}
+
+pub(crate) fn kpi(label: &str, value: f64, unit: Option<&str>) -> String {
+    let unit = unit.map(|u| format!(" {}", esc(u))).unwrap_or_default();
+    format!("<div class=\"card pml-kpi\"><h3>{}</h3><p><strong>{}</strong>{}</p></div>", esc(label), fmt_num(value), unit)
+}
+
+/// Polyline over `values`, scaled to the series' own min..max (a flat series is drawn mid-height).
+pub(crate) fn sparkline(label: &str, values: &[f64], width: u32, height: u32) -> String {
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let (w, h) = (width as f64, height as f64);
+    let step = w / (values.len() - 1) as f64;
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let y = if hi > lo { h - (v - lo) / (hi - lo) * h } else { h / 2.0 };
+            format!("{},{}", fmt_num(i as f64 * step), fmt_num(y))
+        })
+        .collect();
+    format!(
+        "<svg class=\"pml-sparkline\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" role=\"img\" aria-label=\"{label}: {n} points, min {lo}, max {hi}, last {last}\">\
+         <polyline fill=\"none\" points=\"{points}\"/></svg>",
+        w = width,
+        h = height,
+        label = esc(label),
+        n = values.len(),
+        lo = fmt_num(lo),
+        hi = fmt_num(hi),
+        last = fmt_num(values[values.len() - 1]),
+        points = points.join(" ")
+    )
+}
+
+/// Check that including `id` from the document chain `chain` (outermost first, starting with the
+/// page being rendered) neither forms a cycle nor nests deeper than `max_depth` includes.
+pub(crate) fn check_include(chain: &[String], id: &str, max_depth: usize) -> Result<()> {
+    let path = || chain.iter().map(String::as_str).chain([id]).collect::<Vec<_>>().join(" -> ");
+    if chain.iter().any(|c| c == id) {
+        bail!("include cycle: {}", path());
+    }
+    if chain.len() > max_depth {
+        bail!("include depth {} exceeds the limit of {}: {}", chain.len(), max_depth, path());
+    }
+    Ok(())
+}
+
+/// Link targets Proofdown may emit: http(s), mailto, in-page anchors and relative paths.
+/// Anything else (`javascript:`, `data:`, protocol-relative `//host`) is an error.
+pub(crate) fn safe_href(href: &str) -> Result<String> {
+    let h = href.trim();
+    let lower = h.to_ascii_lowercase();
+    let scheme_ok = ["https://", "http://", "mailto:"].iter().any(|p| lower.starts_with(p));
+    let relative = !h.is_empty()
+        && !h.starts_with("//")
+        && !h.chars().any(|c| c.is_control() || c.is_whitespace())
+        && h.split(['/', '?', '#']).next().is_none_or(|first| !first.contains(':'));
+    if !(scheme_ok || relative) {
+        bail!("unsafe link target {:?} (allowed: http(s), mailto, #anchor, relative paths)", href);
+    }
+    Ok(esc(h))
+}
+
+/// Heading levels are clamped to h1..h6.
+pub(crate) fn heading(level: u8, inner: &str) -> String {
+    let l = level.clamp(1, 6);
+    format!("<h{}>{}</h{}>", l, inner, l)
+}
+
+pub(crate) fn list(ordered: bool, items: &[String]) -> String {
+    let tag = if ordered { "ol" } else { "ul" };
+    let mut out = format!("<{}>", tag);
+    for item in items {
+        out.push_str(&format!("<li>{}</li>", item));
+    }
+    out.push_str(&format!("</{}>", tag));
+    out
+}
+
+/// Fenced code; `lang` becomes a `language-*` class only when it is a plain identifier.
+pub(crate) fn code_block(lang: Option<&str>, text: &str) -> String {
+    match lang.filter(|l| !l.is_empty() && l.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))) {
+        Some(l) => format!("<pre><code class=\"language-{}\">{}</code></pre>", l, esc(text)),
+        None => format!("<pre><code>{}</code></pre>", esc(text)),
+    }
+}
+
+/// Table with `<thead>` column headers (`scope="col"`) and one `<tbody>` row per entry; cells are rendered inlines.
+pub(crate) fn table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut out = String::from("<table><thead><tr>");
+    for h in header {
+        out.push_str(&format!("<th scope=\"col\">{}</th>", h));
+    }
+    out.push_str("</tr></thead><tbody>");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in row {
+            out.push_str(&format!("<td>{}</td>", cell));
+        }
+        out.push_str("</tr>");
+    }
+    out.push_str("</tbody></table>");
+    out
+}
+
+/// Inline image; `src` must already point at verified bytes.
+pub(crate) fn image(src: &str, alt: &str) -> String {
+    format!("<img src=\"{}\" alt=\"{}\" loading=\"lazy\" style=\"max-width:100%;height:auto\">", esc(src), esc(alt))
+}
+
+/// Escape text for element content and double-quoted attributes.
+pub(crate) fn esc(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_attributes_are_bounded() {
+        assert_eq!(int_attr("grid", "cols", None, GRID_COLS, 3).unwrap(), 3);
+        assert_eq!(int_attr("grid", "cols", Some("6"), GRID_COLS, 3).unwrap(), 6);
+        let err = int_attr("grid", "cols", Some("7"), GRID_COLS, 3).unwrap_err().to_string();
+        assert_eq!(err, "<grid> cols=7 is out of range 1..=6");
+        assert!(int_attr("grid", "gap", Some("16px"), GRID_GAP, 16).is_err());
+        assert!(int_attr("gallery", "cols", Some("+3"), GALLERY_COLS, 3).is_err());
+        assert!(int_attr("gallery", "cols", Some("1"), GALLERY_COLS, 3).is_err());
+        assert!(required_attr("section", "title", Some("  ")).is_err());
+    }
+
+    #[test]
+    fn placement_and_shared_bounds() {
+        assert!(check_placement(Some("tab"), Some("tabs")).is_ok());
+        assert!(check_placement(Some("artifact.image"), Some("gallery")).is_ok());
+        assert!(check_placement(Some("grid"), None).is_ok());
+        assert_eq!(check_placement(Some("tab"), Some("grid")).unwrap_err().to_string(), "<tab> is only allowed directly inside <tabs>");
+        assert_eq!(check_placement(Some("image"), None).unwrap_err().to_string(), "<image> is only allowed inside <gallery>");
+        assert_eq!(check_placement(None, Some("tabs")).unwrap_err().to_string(), "<tabs> may only contain <tab> components");
+        assert_eq!(check_placement(Some("card"), Some("gallery")).unwrap_err().to_string(), "<gallery> may only contain <image> components");
+
+        assert_eq!(bounded_attr("artifact.sparkline", "width", None).unwrap(), 120);
+        assert!(bounded_attr("artifact.sparkline", "height", Some("201")).is_err());
+        assert!(bounded_attr("card", "cols", Some("3")).is_err());
+        assert_eq!(gauge_range(5.0, 5.0).unwrap_err().to_string(), "<artifact.gauge> min=5 must be below max=5");
+
+        assert!(check_viewer("log", "raw", Some("raw")).is_ok());
+        assert_eq!(check_viewer("log", "raw", None).unwrap_err().to_string(), "artifact.viewer requires kind attribute");
+        assert_eq!(check_viewer("log", "raw", Some("llm-proof")).unwrap_err().to_string(), "artifact.viewer kind=llm-proof cannot show log (render: raw)");
+        assert!(check_viewer("log", "markdown", Some("markdown")).unwrap_err().to_string().starts_with("artifact.viewer kind must be one of "));
+        assert!(check_image("image", "shot", "image/png; charset=binary").is_ok());
+        assert_eq!(check_image("image", "doc", "text/plain").unwrap_err().to_string(), "<image> doc has media type text/plain, expected image/*");
+    }
+
+    #[test]
+    fn number_attributes_and_thresholds() {
+        assert_eq!(num_attr("artifact.gauge", "max", Some("92.5"), 100.0).unwrap(), 92.5);
+        assert_eq!(num_attr("artifact.gauge", "min", Some("-10"), 0.0).unwrap(), -10.0);
+        assert_eq!(num_attr("artifact.gauge", "min", None, 0.0).unwrap(), 0.0);
+        for bad in ["1e3", "NaN", "inf", ".5", "5.", "+1", " 1"] {
+            assert!(num_attr("artifact.gauge", "min", Some(bad), 0.0).is_err(), "{}", bad);
+        }
+        let higher = Thresholds { warn: 60.0, ok: 80.0 };
+        assert_eq!([higher.class(85.0), higher.class(60.0), higher.class(10.0)], ["ok", "warn", "err"]);
+        let lower = Thresholds { warn: 5.0, ok: 0.0 };
+        assert_eq!([lower.class(0.0), lower.class(3.0), lower.class(9.0)], ["ok", "warn", "err"]);
+        assert!(Thresholds::from_attrs("artifact.gauge", Some("60"), None).is_err());
+        assert_eq!(Thresholds::from_attrs("artifact.gauge", None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn json_pointers_select_numbers_and_series() {
+        let doc = serde_json::json!({"total": {"pct": 87.25}, "history": [70, 80.5, 87.25], "name": "x"});
+        assert_eq!(json_number("artifact.kpi", &doc, "/total/pct").unwrap(), 87.25);
+        assert!(json_number("artifact.kpi", &doc, "/name").is_err());
+        assert!(json_number("artifact.kpi", &doc, "total/pct").is_err());
+        assert!(json_number("artifact.kpi", &doc, "/missing").is_err());
+        assert_eq!(json_series("artifact.sparkline", &doc, "/history").unwrap(), vec![70.0, 80.5, 87.25]);
+        assert!(json_series("artifact.sparkline", &serde_json::json!([1]), "").is_err());
+        assert!(json_series("artifact.sparkline", &serde_json::json!([1, "2"]), "").is_err());
+    }
+
+    #[test]
+    fn charts_are_deterministic_svg() {
+        let html = gauge("Coverage <total>", 75.0, 0.0, 100.0, Some(Thresholds { warn: 60.0, ok: 80.0 })).unwrap();
+        assert!(html.contains("aria-label=\"Coverage &lt;total&gt;: 75 (range 0 to 100)\""));
+        assert!(html.contains("<path class=\"arc warn\" d=\"M10 60 A50 50 0 0 1 95.36 24.64\"/>"));
+        assert!(!gauge("x", 0.0, 0.0, 1.0, None).unwrap().contains("class=\"arc"));
+        assert!(gauge("x", 1.0, 5.0, 5.0, None).is_err());
+        assert_eq!(kpi("Passed", 41.0, Some("tests")), "<div class=\"card pml-kpi\"><h3>Passed</h3><p><strong>41</strong> tests</p></div>");
+        let svg = sparkline("Duration", &[1.0, 3.0, 2.0], 100, 20);
+        assert!(svg.contains("points=\"0,20 50,0 100,10\""));
+        assert!(svg.contains("aria-label=\"Duration: 3 points, min 1, max 3, last 2\""));
+        assert!(sparkline("flat", &[4.0, 4.0], 40, 12).contains("points=\"0,6 40,6\""));
+    }
+
+    #[test]
+    fn components_accept_only_registered_attributes() {
+        assert!(check_attrs("grid", ["cols", "gap"]).is_ok());
+        assert_eq!(check_attrs("grid", ["style"]).unwrap_err().to_string(), "<grid> does not accept attribute \"style\" (allowed: cols, gap)");
+        assert_eq!(check_attrs("marquee", []).unwrap_err().to_string(), "unknown component: marquee");
+    }
+
+    #[test]
+    fn tabs_are_labelled_radio_groups() {
+        let ids = Ids::default();
+        let id = ids.next("tabs");
+        let html = tabs(&id, &[
+            Tab { title: "Unit".into(), body: "<p>u</p>".into() },
+            Tab { title: "E2E <ui>".into(), body: "<p>e</p>".into() },
+        ])
+        .unwrap();
+        assert!(html.contains("<input type=\"radio\" name=\"pml-tabs-1\" id=\"pml-tabs-1-tab-1\" checked><label for=\"pml-tabs-1-tab-1\""));
+        assert!(html.contains("<input type=\"radio\" name=\"pml-tabs-1\" id=\"pml-tabs-1-tab-2\"><label"));
+        assert!(html.contains("aria-labelledby=\"pml-tabs-1-tab-2-label\"><p>e</p></div>"));
+        assert!(html.contains("E2E &lt;ui&gt;"));
+        assert_eq!(ids.next("section"), "pml-section-2");
+        assert!(tabs("x", &[]).is_err());
+    }
+
+    #[test]
+    fn includes_are_bounded_and_acyclic() {
+        let chain: Vec<String> = ["front page", "release-notes"].iter().map(|s| s.to_string()).collect();
+        assert!(check_include(&chain, "changelog", 3).is_ok());
+        let err = check_include(&chain, "release-notes", 3).unwrap_err().to_string();
+        assert_eq!(err, "include cycle: front page -> release-notes -> release-notes");
+        let err = check_include(&chain, "changelog", 1).unwrap_err().to_string();
+        assert_eq!(err, "include depth 2 exceeds the limit of 1: front page -> release-notes -> changelog");
+    }
+
+    #[test]
+    fn only_safe_link_targets_are_emitted() {
+        assert_eq!(safe_href("https://example.com/?a=1&b=2").unwrap(), "https://example.com/?a=1&amp;b=2");
+        assert_eq!(safe_href("../a/coverage/#total").unwrap(), "../a/coverage/#total");
+        assert!(safe_href("#failures").is_ok());
+        assert!(safe_href("mailto:qa@example.com").is_ok());
+        assert!(safe_href("javascript:alert(1)").is_err());
+        assert!(safe_href("JaVaScRiPt:alert(1)").is_err());
+        assert!(safe_href("data:text/html,<b>").is_err());
+        assert!(safe_href("//evil.example.com").is_err());
+        assert!(safe_href("java\tscript:x").is_err());
+    }
+
+    #[test]
+    fn block_markup_is_semantic_and_escaped() {
+        assert_eq!(code_block(Some("rust"), "a < b"), "<pre><code class=\"language-rust\">a &lt; b</code></pre>");
+        assert_eq!(code_block(Some("x\" onload=\"y"), "z"), "<pre><code>z</code></pre>");
+        assert_eq!(list(true, &["one".into()]), "<ol><li>one</li></ol>");
+        assert_eq!(
+            table(&["File".into()], &[vec!["src/lib.rs".into()]]),
+            "<table><thead><tr><th scope=\"col\">File</th></tr></thead><tbody><tr><td>src/lib.rs</td></tr></tbody></table>"
+        );
+        assert_eq!(heading(9, "x"), "<h6>x</h6>");
+    }
+
+    #[test]
+    fn gallery_renders_linked_figures() {
+        let html = gallery(3, &[Figure { href: "/a/shot/".into(), src: "/assets/shot.png".into(), alt: "Login \"ok\"".into(), caption: "Login".into() }]).unwrap();
+        assert_eq!(
+            html,
+            "<div class=\"pml-gallery cols-3\"><figure><a href=\"/a/shot/\"><img src=\"/assets/shot.png\" alt=\"Login &quot;ok&quot;\" loading=\"lazy\"></a><figcaption>Login</figcaption></figure></div>"
+        );
+    }
+
+    #[test]
+    fn component_render_matrix() {
+        assert_eq!(compatible_renders("artifact.summary"), Some(vec!["summary:test"]));
+        assert_eq!(compatible_renders("artifact.viewer"), Some(vec!["llm-proof", "text", "raw"]));
+        assert!(compatible_renders("artifact.kpi").unwrap().contains(&"table:coverage"));
+        assert!(!compatible_renders("artifact.markdown").unwrap().contains(&"json"));
+        assert_eq!(compatible_renders("artifact.link"), None);
+        assert_eq!(
+            check_render("artifact.table", "tests-summary", "summary:test").unwrap_err().to_string(),
+            "<artifact.table> cannot show tests-summary (render: summary:test); expected table:coverage"
+        );
+        assert!(check_render("include.pml", "page", "proofdown:ast").is_ok());
+        assert!(check_render("artifact.link", "any", "repo:bundle").is_ok());
+        // Every matrix entry is a registered component
+        assert!(COMPONENT_RENDERS.iter().all(|(c, _)| check_attrs(c, std::iter::empty()).is_ok()));
+        // ... and every hint is a registered renderer
+        assert!(COMPONENT_RENDERS.iter().flat_map(|(_, r)| r.iter()).all(|h| render::lookup(h).is_some()));
+    }
+}
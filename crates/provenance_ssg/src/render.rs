@@ -1,105 +1,306 @@
-use std::collections::BTreeMap;
-
-#[derive(Debug, Clone)]
-pub struct ArtifactView<'a> {
-    pub id: &'a str,
-    pub title: &'a str,
-    pub render: &'a str,
-    pub media_type: &'a str,
-    pub path_rel: &'a str,
-    pub verified: bool,
-    pub download_href: String,
-}
-
-pub use renderers::{Coverage, TestSummary};
-
-pub fn page_base(inner_html: String) -> String {
-    format!(
-        "<!doctype html><html lang=\"en\"><head><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><link rel=\"stylesheet\" href=\"/assets/site.css\"></head><body><a href=\"#main\" class=\"skip-link\">Skip to content</a><div class=\"container\"><header class=\"topbar\" role=\"banner\"><nav aria-label=\"Global\" class=\"global-nav\"><a href=\"/index.html\">Home</a><a href=\"/artifacts/\">Artifacts</a><a href=\"/badge/\">Badges</a><a href=\"/artifacts/#search\">Search</a></nav></header><main id=\"main\" role=\"main\">{}</main></div></body></html>",
-        inner_html
-    )
-}
+pub use renderers::{decode_text, lookup, Coverage, EvidenceLink, Input, Renderer, TestSummary, REGISTRY};
 
-pub fn index_page(
-    title: &str,
-    commit: &str,
-    kpis: BTreeMap<&str, String>,
-    featured: Vec<ArtifactView>,
-) -> String {
-    let mut inner = String::new();
-    inner.push_str(&format!("<header><h1>{} — {}</h1></header>", html_escape(title), html_escape(commit)));
-    if !kpis.is_empty() {
-        inner.push_str("<div class=\"cards\">");
-        for (k, v) in kpis.iter() {
-            inner.push_str(&format!("<div class=\"card\"><h3>{}</h3><p><strong>{}</strong></p></div>", html_escape(k), html_escape(v)));
-        }
-        inner.push_str("</div>");
-    }
-    inner.push_str("<h2>Artifacts</h2><div class=\"cards\">");
-    for a in featured.iter() {
-        inner.push_str(&format!(
-            "<div class=\"card\"><h3>{}</h3><p class=\"muted\">{}</p><p>{}</p><p><a href=\"/a/{}/\">View</a> · <a href=\"{}\">Download</a></p></div>",
-            html_escape(a.title),
-            html_escape(a.id),
-            if a.verified { "<span class=\"badge ok\">verified</span>".to_string() } else { "<span class=\"badge err\">digest mismatch</span>".to_string() },
-            html_escape(a.id),
-            html_escape(&a.download_href)
-        ));
-    }
-    inner.push_str("</div>");
-    page_base(inner)
+/// Whether `media_type` is one the `image` hint accepts (`image/*`, parameters and case ignored).
+pub(crate) fn is_image(media_type: &str) -> bool {
+    lookup("image").is_some_and(|r| r.accepts(media_type))
 }
 
-pub fn artifact_page(a: &ArtifactView, body_html: &str) -> String {
-    let mut inner = String::new();
-    inner.push_str(&format!("<header><nav aria-label=\"Breadcrumb\"><a href=\"/index.html\">Home</a> / <span>{}</span></nav><h1>{}</h1><p class=\"muted\">{}</p></header>", html_escape(a.title), html_escape(a.title), html_escape(a.id)));
-    inner.push_str(&format!("<p>{}</p>", if a.verified { "<span class=\"badge ok\">verified</span>" } else { "<span class=\"badge err\">digest mismatch</span>" }));
-    inner.push_str(&format!("<article>{}</article>", body_html));
-    inner.push_str(&format!("<p><a href=\"{}\">Download raw</a></p>", html_escape(&a.download_href)));
-    page_base(inner)
-}
+/// Design tokens of the built-in light theme; every color, font and size in `SITE_RULES` refers to one.
+const LIGHT_TOKENS: &str = r#":root{color-scheme:light dark;
+    --color-bg:#fafafa;--color-text:#111;--color-muted:#777;--color-subtle:#555;--color-border:#eee;--color-border-strong:#ddd;
+    --color-surface:#fff;--color-surface-alt:#f7f7f7;--color-surface-hover:#eee;--color-badge-text:#fff;
+    --color-ok:#28a745;--color-warn:#ff9800;--color-err:#d32f2f;
+    --font-body:system-ui,-apple-system,Segoe UI,Roboto,Ubuntu,Cantarell,Noto Sans,sans-serif;--font-mono:ui-monospace,SFMono-Regular,Menlo,Consolas,monospace;
+    --space-gap:16px;--space-radius:8px;--space-radius-sm:6px;--space-container:1040px}
+    "#;
 
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
-}
+/// Built-in dark palette, applied when the reader's system prefers a dark color scheme.
+const DARK_TOKENS: &str = r#"@media (prefers-color-scheme: dark){:root{
+    --color-bg:#0f1115;--color-text:#e6e6e6;--color-muted:#9aa0a6;--color-subtle:#b0b4ba;--color-border:#2a2e35;--color-border-strong:#3a3f48;
+    --color-surface:#171a21;--color-surface-alt:#1f232b;--color-surface-hover:#2a2e35;--color-badge-text:#fff;
+    --color-ok:#2e7d32;--color-warn:#b26a00;--color-err:#c62828}}
+    "#;
 
-pub use renderers::{render_coverage, render_image, render_json_pretty, render_markdown, render_tests_summary};
-
-/// Site CSS (extracted from previous inline style), plus minimal layout for top bar and sidebars.
-pub fn site_css() -> &'static str {
-    r#"body{font-family:system-ui,-apple-system,Segoe UI,Roboto,Ubuntu,Cantarell,Noto Sans,sans-serif;margin:0;padding:0;color:#111;background:#fafafa}
-    a{color:inherit;text-decoration:underline}
-    .container{max-width:1040px;margin:0 auto;padding:24px}
-    .topbar{padding:8px 0 16px 0;border-bottom:1px solid #eee;margin-bottom:16px}
-    .global-nav{display:flex;gap:16px;flex-wrap:wrap}
-    .cards{display:grid;grid-template-columns:repeat(auto-fit,minmax(280px,1fr));gap:16px}
-    .card{border:1px solid #eee;border-radius:8px;padding:16px;background:#fff}
-    header.page{padding:16px 0;border-bottom:1px solid #eee;margin-bottom:24px}
+const SITE_RULES: &str = r#"body{font-family:var(--font-body);margin:0;padding:0;color:var(--color-text);background:var(--color-bg)}
+    a{color:var(--color-link,inherit);text-decoration:underline}
+    .container{max-width:var(--space-container);margin:0 auto;padding:24px}
+    .topbar{padding:8px 0 16px 0;border-bottom:1px solid var(--color-border);margin-bottom:16px}
+    .global-nav{display:flex;gap:var(--space-gap);flex-wrap:wrap}
+    .cards{display:grid;grid-template-columns:repeat(auto-fit,minmax(280px,1fr));gap:var(--space-gap)}
+    .card{border:1px solid var(--color-border);border-radius:var(--space-radius);padding:16px;background:var(--color-surface)}
+    header.page{padding:16px 0;border-bottom:1px solid var(--color-border);margin-bottom:24px}
     table{width:100%;border-collapse:collapse}
     thead th{scope:col}
-    th,td{padding:8px;border-bottom:1px solid #eee;text-align:left}
-    .badge{display:inline-block;padding:2px 8px;border-radius:6px;font-size:12px;color:#fff}
-    .ok{background:#28a745}
-    .warn{background:#ff9800}
-    .err{background:#d32f2f}
-    code, pre{background:#f7f7f7;border-radius:6px;padding:2px 6px}
+    th,td{padding:8px;border-bottom:1px solid var(--color-border);text-align:left}
+    .badge{display:inline-block;padding:2px 8px;border-radius:var(--space-radius-sm);font-size:12px;color:var(--color-badge-text)}
+    .ok{background:var(--color-ok)}
+    .warn{background:var(--color-warn)}
+    .err{background:var(--color-err)}
+    code, pre{font-family:var(--font-mono);background:var(--color-surface-alt);border-radius:var(--space-radius-sm);padding:2px 6px}
     pre{padding:12px;overflow-x:auto}
-    .muted{color:#777;font-size:14px}
+    .muted{color:var(--color-muted);font-size:14px}
     .skip-link{position:absolute;left:-10000px;top:auto;width:1px;height:1px;overflow:hidden}
     .skip-link:focus{position:static;width:auto;height:auto}
     /* Content layout with optional left/right sidebars */
-    .content-grid{display:grid;gap:16px;grid-template-columns:220px 1fr 260px;align-items:start}
+    .content-grid{display:grid;gap:var(--space-gap);grid-template-columns:220px 1fr 260px;align-items:start}
     .left-nav{position:sticky;top:12px}
     .right-meta{position:sticky;top:12px}
     @media (max-width: 1024px){.content-grid{grid-template-columns:1fr}.left-nav,.right-meta{position:relative;top:auto}}
     /* Filters/search form */
     form.filters{display:grid;gap:12px;margin:16px 0}
     form.filters .row{display:grid;grid-template-columns:160px 1fr;gap:8px;align-items:center}
-    input[type=search],select{padding:8px;border:1px solid #ddd;border-radius:6px;background:#fff}
-    button{padding:8px 12px;border:1px solid #ddd;border-radius:6px;background:#f7f7f7;cursor:pointer}
-    button:hover{background:#eee}
+    input[type=search],select{padding:8px;border:1px solid var(--color-border-strong);border-radius:var(--space-radius-sm);background:var(--color-surface);color:inherit}
+    button{padding:8px 12px;border:1px solid var(--color-border-strong);border-radius:var(--space-radius-sm);background:var(--color-surface-alt);color:inherit;cursor:pointer}
+    button:hover{background:var(--color-surface-hover)}
+    /* Static listing links (artifacts index sidebar) */
+    nav.facets h3{font-size:14px;margin:16px 0 4px 0;color:var(--color-subtle)}
+    nav.facets ul{list-style:none;margin:0;padding:0}
+    nav.facets a[aria-current=page]{font-weight:600;text-decoration:none}
+    /* Full-text results */
+    ol.search-results{padding-left:20px}
+    ol.search-results li{margin:0 0 16px 0}
+    ol.search-results p{margin:4px 0 0 0}
     dl.meta{display:grid;grid-template-columns:120px 1fr;gap:8px}
-    dl.meta dt{color:#555}
-    dl.meta dd{margin:0}
-    "#
+    /* Proofdown gauges, KPIs and sparklines */
+    .pml-gauge{margin:0;max-width:200px;text-align:center}
+    .pml-gauge path{fill:none;stroke-width:10;stroke-linecap:round}
+    .pml-gauge .track{stroke:var(--color-border-strong)}
+    .pml-gauge .arc.ok{stroke:var(--color-ok)}
+    .pml-gauge .arc.warn{stroke:var(--color-warn)}
+    .pml-gauge .arc.err{stroke:var(--color-err)}
+    .pml-gauge .arc.neutral{stroke:var(--color-subtle)}
+    .pml-gauge text{fill:currentColor;font-size:16px}
+    .pml-sparkline polyline{stroke:var(--color-subtle);stroke-width:1.5}
+    /* LLM proof claims */
+    ol.claims{padding-left:20px}
+    ol.claims>li{margin:0 0 16px 0;padding:8px 12px;border-left:3px solid var(--color-border)}
+    ol.claims>li.flagged{border-left-color:var(--color-err)}
+    ol.claims h4{font-size:14px;margin:8px 0 4px 0;color:var(--color-subtle)}
+    .claim-flag{color:var(--color-err)}
+    /* Text and raw fallback viewers */
+    pre.text-lines .ln{color:var(--color-muted);user-select:none}
+    pre.hex-dump{font-size:12px}
+    dl.meta dt{color:var(--color-subtle)}
+    dl.meta dd{margin:0;overflow-wrap:anywhere}
+    code.digest{font-size:12px;word-break:break-all}
+    pre.copy{user-select:all;white-space:pre-wrap;word-break:break-all;font-size:12px}
+    /* Proofdown structural components (see proofdown.rs) */
+    section.pml-section{margin:24px 0}
+    .pml-tabs{display:flex;flex-wrap:wrap;gap:0 4px;margin:16px 0}
+    .pml-tabs > input{position:absolute;opacity:0;width:1px;height:1px}
+    .pml-tabs > label{order:1;padding:8px 12px;border:1px solid var(--color-border);border-bottom:none;border-radius:var(--space-radius-sm) var(--space-radius-sm) 0 0;background:var(--color-surface-alt);cursor:pointer}
+    .pml-tabs > input:checked + label{background:var(--color-surface);font-weight:600}
+    .pml-tabs > input:focus-visible + label{outline:2px solid var(--color-link,currentColor);outline-offset:2px}
+    .pml-tabs > .pml-tab-panel{order:2;display:none;width:100%;border:1px solid var(--color-border);padding:16px;background:var(--color-surface)}
+    .pml-tabs > input:checked + label + .pml-tab-panel{display:block}
+    .pml-gallery{display:grid;gap:var(--space-gap);grid-template-columns:repeat(3,minmax(0,1fr))}
+    .pml-gallery.cols-2{grid-template-columns:repeat(2,minmax(0,1fr))}
+    .pml-gallery.cols-4{grid-template-columns:repeat(4,minmax(0,1fr))}
+    .pml-gallery.cols-5{grid-template-columns:repeat(5,minmax(0,1fr))}
+    .pml-gallery.cols-6{grid-template-columns:repeat(6,minmax(0,1fr))}
+    .pml-gallery figure{margin:0}
+    .pml-gallery img{display:block;width:100%;height:auto;border-radius:var(--space-radius-sm)}
+    .pml-gallery figcaption{color:var(--color-muted);font-size:14px;margin-top:4px}
+    @media (max-width: 640px){.pml-gallery[class]{grid-template-columns:repeat(2,minmax(0,1fr))}}
+    "#;
+
+/// Site CSS with the built-in light and dark themes.
+pub fn site_css() -> String {
+    themed_site_css("", "")
+}
+
+/// Site CSS with theme overrides (declarations of `--color-*`, `--font-*`, `--space-*` custom
+/// properties, see `theme.rs`). Light overrides come before the built-in dark palette so dark mode
+/// still applies unless `dark_overrides` replaces it.
+pub(crate) fn themed_site_css(overrides: &str, dark_overrides: &str) -> String {
+    let mut css = String::from(LIGHT_TOKENS);
+    if !overrides.is_empty() {
+        css.push_str(&format!(":root{{{}}}\n", overrides));
+    }
+    css.push_str(DARK_TOKENS);
+    if !dark_overrides.is_empty() {
+        css.push_str(&format!("@media (prefers-color-scheme: dark){{:root{{{}}}}}\n", dark_overrides));
+    }
+    css.push_str(SITE_RULES);
+    css
+}
+
+/// Client-side filtering for `/artifacts/`. Served as `assets/search.js` (never inlined, so a
+/// `script-src 'self'` CSP works); reads `search_index.json` and filters/sorts the table rows in place.
+pub fn search_js() -> &'static str {
+    r#"(function () {
+  'use strict';
+  var form = document.getElementById('artifact-filters');
+  var table = document.getElementById('artifacts-table');
+  if (!form || !table || !window.fetch) { return; }
+  var tbody = table.tBodies[0];
+  var count = document.getElementById('artifact-count');
+  var rows = {};
+  Array.prototype.forEach.call(tbody.rows, function (tr) { rows[tr.getAttribute('data-id')] = tr; });
+
+  // Seed controls from the query string so links to /artifacts/?q=... keep working
+  var params = new URLSearchParams(window.location.search);
+  ['q', 'kind', 'verified', 'media', 'sort'].forEach(function (name) {
+    var el = form.elements[name];
+    if (el && params.has(name)) { el.value = params.get(name); }
+  });
+
+  function haystack(item) {
+    return [item.id, item.title, item.render, item.media_type, item.group || '', (item.tags || []).join(' '), item.excerpt || '']
+      .join(' ').toLowerCase();
+  }
+
+  function mediaMatches(pattern, media) {
+    if (!pattern) { return true; }
+    if (pattern.slice(-2) === '/*') { return media.indexOf(pattern.slice(0, -1)) === 0; }
+    return media === pattern;
+  }
+
+  function apply(items) {
+    var q = (form.elements.q.value || '').trim().toLowerCase();
+    var terms = q ? q.split(/\s+/) : [];
+    var kind = form.elements.kind.value;
+    var verified = form.elements.verified.value;
+    var media = form.elements.media.value;
+    var sort = form.elements.sort.value || 'id';
+
+    var shown = items.filter(function (item) {
+      if (kind && item.render !== kind) { return false; }
+      if (verified && String(item.verified) !== verified) { return false; }
+      if (!mediaMatches(media, item.media_type)) { return false; }
+      var text = item._text;
+      return terms.every(function (t) { return text.indexOf(t) !== -1; });
+    });
+    shown.sort(function (a, b) {
+      var ka = String(a[sort] || ''), kb = String(b[sort] || '');
+      return ka < kb ? -1 : ka > kb ? 1 : (a.id < b.id ? -1 : 1);
+    });
+
+    var visible = {};
+    shown.forEach(function (item) {
+      var tr = rows[item.id];
+      if (tr) { tbody.appendChild(tr); visible[item.id] = true; }
+    });
+    Object.keys(rows).forEach(function (id) { rows[id].hidden = !visible[id]; });
+    if (count) { count.textContent = shown.length + ' of ' + items.length + ' artifacts'; }
+  }
+
+  fetch(form.getAttribute('data-index'), { credentials: 'same-origin' })
+    .then(function (res) { if (!res.ok) { throw new Error(res.status); } return res.json(); })
+    .then(function (items) {
+      items.forEach(function (item) { item._text = haystack(item); });
+      form.hidden = false;
+      var run = function () { apply(items); };
+      form.addEventListener('submit', function (ev) { ev.preventDefault(); run(); });
+      form.addEventListener('input', run);
+      form.addEventListener('change', run);
+      run();
+    })
+    .catch(function () { /* keep the full server-rendered table */ });
+})();
+"#
+}
+
+/// Full-text results page script, served as `assets/fulltext.js`. Tokenizer and shard hash
+/// mirror `fulltext::tokenize` and `fulltext::shard_of`.
+pub fn fulltext_js() -> &'static str {
+    r#"(function () {
+  'use strict';
+  var list = document.getElementById('search-results');
+  var status = document.getElementById('search-status');
+  if (!list || !window.fetch || !window.TextEncoder) { return; }
+  var base = list.getAttribute('data-base') || '';
+  var q = new URLSearchParams(window.location.search).get('q') || '';
+  var input = document.getElementById('q');
+  if (input) { input.value = q; }
+
+  function tokenize(text) {
+    return (text.match(/[\p{L}\p{N}_]+/gu) || [])
+      .filter(function (t) { var n = Array.from(t).length; return n >= 2 && n <= 40; })
+      .map(function (t) { return t.toLowerCase(); });
+  }
+
+  function shardOf(token, shards) {
+    var h = 0x811c9dc5;
+    new TextEncoder().encode(token).forEach(function (b) {
+      h ^= b;
+      h = Math.imul(h, 0x01000193) >>> 0;
+    });
+    return h % shards;
+  }
+
+  function getJson(path) {
+    return fetch(base + path, { credentials: 'same-origin' }).then(function (res) {
+      if (!res.ok) { throw new Error(path + ': ' + res.status); }
+      return res.json();
+    });
+  }
+
+  function hex2(n) { return (n < 16 ? '0' : '') + n.toString(16); }
+
+  // Snippet with query tokens wrapped in <mark>, built with DOM nodes (never innerHTML)
+  function snippet(text, tokens) {
+    var p = document.createElement('p');
+    var re = /[\p{L}\p{N}_]+/gu, last = 0, m;
+    while ((m = re.exec(text)) !== null) {
+      if (tokens.indexOf(m[0].toLowerCase()) === -1) { continue; }
+      p.appendChild(document.createTextNode(text.slice(last, m.index)));
+      var mark = document.createElement('mark');
+      mark.textContent = m[0];
+      p.appendChild(mark);
+      last = m.index + m[0].length;
+    }
+    p.appendChild(document.createTextNode(text.slice(last)));
+    return p;
+  }
+
+  var tokens = tokenize(q).filter(function (t, i, all) { return all.indexOf(t) === i; });
+  if (!tokens.length) { return; }
+  status.textContent = 'Searching…';
+
+  getJson('/search/meta.json').then(function (meta) {
+    var needed = {};
+    tokens.forEach(function (t) { needed[shardOf(t, meta.shards)] = true; });
+    var shardIds = Object.keys(needed);
+    return Promise.all(shardIds.map(function (s) { return getJson('/search/shard-' + hex2(Number(s)) + '.json'); }))
+      .then(function (loaded) {
+        var byShard = {};
+        shardIds.forEach(function (s, i) { byShard[s] = loaded[i]; });
+        // Every token must occur in the artifact; the snippet is the first token's passage
+        var hits = null;
+        tokens.forEach(function (t) {
+          var postings = byShard[shardOf(t, meta.shards)][t] || [];
+          var docs = {};
+          postings.forEach(function (pair) { docs[pair[0]] = pair[1]; });
+          if (hits === null) { hits = docs; return; }
+          Object.keys(hits).forEach(function (d) { if (!(d in docs)) { delete hits[d]; } });
+        });
+        var docIds = Object.keys(hits || {}).map(Number).sort(function (a, b) { return a - b; });
+        return Promise.all(docIds.map(function (d) {
+          var doc = meta.docs[d];
+          return getJson('/search/passages/' + encodeURIComponent(doc.id) + '.json').then(function (passages) {
+            return { doc: doc, passage: passages[hits[d]] || '' };
+          });
+        }));
+      });
+  }).then(function (results) {
+    status.textContent = results.length + (results.length === 1 ? ' artifact matches' : ' artifacts match');
+    results.forEach(function (r) {
+      var li = document.createElement('li');
+      var a = document.createElement('a');
+      a.href = base + '/a/' + encodeURIComponent(r.doc.id) + '/#:~:text=' + encodeURIComponent(tokens[0]);
+      a.textContent = r.doc.title;
+      var kind = document.createElement('span');
+      kind.className = 'muted';
+      kind.textContent = ' ' + r.doc.id + ' · ' + r.doc.render;
+      li.appendChild(a);
+      li.appendChild(kind);
+      li.appendChild(snippet(r.passage, tokens));
+      list.appendChild(li);
+    });
+  }).catch(function (err) {
+    status.textContent = 'Search index unavailable (' + err.message + ')';
+  });
+})();
+"#
 }
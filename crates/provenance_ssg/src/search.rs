@@ -0,0 +1,101 @@
+// Finding artifacts: the artifacts index with its pre-rendered facet listings (the no-JS filters),
+// `search_index.json` for the client-side filter, and the full-text index with its search page.
+// Only verified content is indexed; a mismatched file is not trusted evidence.
+
+use anyhow::Result;
+use std::fs;
+
+use crate::fulltext;
+use crate::ssg::{ArtifactViewExt, Site};
+use frontend as fe;
+
+const SEARCH_EXCERPT_CHARS: usize = 240;
+
+/// One artifact in `search_index.json` (consumed by assets/search.js on the artifacts index).
+#[derive(serde::Serialize)]
+struct SearchItem<'a> {
+    id: &'a str,
+    title: &'a str,
+    render: &'a str,
+    media_type: &'a str,
+    verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excerpt: Option<String>,
+}
+
+/// Write the artifacts index, its facet listings and both search indexes.
+pub(crate) fn publish(site: &Site, views: &[ArtifactViewExt]) -> Result<()> {
+    let args = site.args;
+
+    // artifacts index
+    let items: Vec<fe::Artifact> = views.iter().map(ArtifactViewExt::summary).collect();
+    let description = format!("All {} artifacts for {}", views.len(), site.at_commit);
+    site.write_page("/artifacts/", format!("All artifacts — {}", site.title), description, fe::render_artifacts_index(&site.base, &items))?;
+
+    // Pre-rendered listings per kind, status and media type
+    for facet in &fe::artifact_facets(&items) {
+        site.write_page(
+            &format!("/{}/", facet.path()),
+            format!("Artifacts: {} — {}", facet.label, site.title),
+            format!("{} artifacts for {}", facet.label, site.at_commit),
+            fe::render_artifacts_listing(&site.base, &items, facet),
+        )?;
+    }
+
+    // search index
+    let sidx: Vec<SearchItem> = views.iter().map(|v| SearchItem {
+        id: &v.artifact.id,
+        title: &v.artifact.title,
+        render: &v.artifact.render,
+        media_type: &v.artifact.media_type,
+        verified: v.verified,
+        group: v.artifact.group.as_deref(),
+        tags: &v.artifact.tags,
+        excerpt: if v.verified && v.artifact.render == "markdown" {
+            fs::read_to_string(args.root.join(&v.artifact.path)).ok().map(|md| markdown_excerpt(&md, SEARCH_EXCERPT_CHARS))
+        } else {
+            None
+        },
+    }).collect();
+    let txt = serde_json::to_string_pretty(&sidx)? + "\n";
+    fs::write(args.out.join("search_index.json"), txt).ok();
+
+    // Full-text index over verified artifact contents (same size limit as inline rendering)
+    let docs: Vec<fulltext::Doc> = views.iter().map(|v| {
+        let src = args.root.join(&v.artifact.path);
+        let passages = match fs::read(&src) {
+            Ok(bytes) if v.verified && bytes.len() <= args.truncate_inline_bytes => fulltext::passages_for(&v.artifact.render, &bytes),
+            _ => Vec::new(),
+        };
+        fulltext::Doc { id: &v.artifact.id, title: &v.artifact.title, render: &v.artifact.render, passages }
+    }).collect();
+    fulltext::write_index(&args.out, &docs)?;
+    let description = format!("Full-text search over the artifacts of {}", site.at_commit);
+    site.write_page("/search/", format!("Search — {}", site.title), description, fe::render_search_page(&site.base))
+}
+
+/// Plain-text excerpt of a Markdown document for the search index: block text only,
+/// whitespace collapsed, cut at `max_chars` characters.
+fn markdown_excerpt(md: &str, max_chars: usize) -> String {
+    let collapsed = fulltext::markdown_passages(md).join(" ");
+    match collapsed.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}…", collapsed[..cut].trim_end()),
+        None => collapsed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_excerpt_strips_syntax_and_truncates() {
+        let md = "# Failures\n\n- **spec_a** failed: `timeout`\n- spec_b\n\n| a | b |\n|---|---|\n| 1 | 2 |\n";
+        assert_eq!(markdown_excerpt(md, 100), "Failures spec_a failed: timeout spec_b a b 1 2");
+        assert_eq!(markdown_excerpt("héllo wörld", 5), "héllo…");
+    }
+}
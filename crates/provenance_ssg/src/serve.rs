@@ -0,0 +1,226 @@
+// Local preview server: serves the generated site over HTTP on localhost and rebuilds
+// whenever the manifest, its signature, the front page or any artifact file changes.
+// Intentionally std-only (no async runtime); this is a developer convenience, not a host.
+
+use anyhow::{Context, Result};
+use manifest_contract as mc;
+use percent_encoding::percent_decode_str;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::ssg::{normalize_base_path, run_with_args, Args};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ServeArgs {
+    #[command(flatten)]
+    pub build: Args,
+
+    /// Interface to bind the preview server to
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Port to bind the preview server to
+    #[arg(long, default_value_t = 8000)]
+    pub port: u16,
+
+    /// Interval in milliseconds between checks for changed inputs
+    #[arg(long, default_value_t = 500u64)]
+    pub poll_ms: u64,
+}
+
+/// Build once, then serve `--out` and rebuild on input changes until the process is killed.
+pub fn serve(opts: ServeArgs) -> Result<()> {
+    let build = opts.build.clone();
+    rebuild(&build);
+
+    let listener = TcpListener::bind((opts.host.as_str(), opts.port))
+        .with_context(|| format!("bind preview server on {}:{}", opts.host, opts.port))?;
+
+    let site_root = build.out.clone();
+    let base = normalize_base_path(&build.base_path);
+    println!("Serving {} at http://{}:{}{}/", build.out.display(), opts.host, opts.port, base);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let root = site_root.clone();
+            let base = base.clone();
+            thread::spawn(move || {
+                if let Err(e) = handle_connection(stream, &root, &base) {
+                    eprintln!("preview: {}", e);
+                }
+            });
+        }
+    });
+
+    let interval = Duration::from_millis(opts.poll_ms.max(50));
+    let mut last = snapshot(&watched_files(&build));
+    loop {
+        thread::sleep(interval);
+        let now = snapshot(&watched_files(&build));
+        if now != last {
+            println!("Change detected, rebuilding");
+            rebuild(&build);
+            last = now;
+        }
+    }
+}
+
+fn rebuild(args: &Args) {
+    // A broken input should not take the server down; keep serving the last good output.
+    if let Err(e) = run_with_args(args.clone()) {
+        eprintln!("rebuild failed: {:#}", e);
+    }
+}
+
+/// Inputs that affect the generated site. Re-read on every poll so that artifacts
+/// added to or removed from the manifest are picked up.
+fn watched_files(args: &Args) -> Vec<PathBuf> {
+    let manifest_path = args.root.join(&args.manifest);
+    let mut files = vec![manifest_path.clone(), manifest_path.with_extension("json.sig")];
+    files.extend(args.schema_path.iter().cloned());
+    files.extend(args.theme.iter().cloned());
+    if let Ok((m, _)) = mc::load_manifest(&manifest_path) {
+        files.push(args.root.join(&m.front_page.markup));
+        files.extend(m.pages.iter().map(|p| args.root.join(&p.markup)));
+        files.extend(m.artifacts.iter().map(|a| args.root.join(&a.path)));
+    }
+    files
+}
+
+fn snapshot(files: &[PathBuf]) -> BTreeMap<PathBuf, Option<(SystemTime, u64)>> {
+    files
+        .iter()
+        .map(|p| {
+            let stamp = fs::metadata(p).ok().and_then(|m| m.modified().ok().map(|t| (t, m.len())));
+            (p.clone(), stamp)
+        })
+        .collect()
+}
+
+fn handle_connection(mut stream: TcpStream, site_root: &Path, base: &str) -> Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+
+    if method != "GET" && method != "HEAD" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain; charset=utf-8", b"method not allowed\n", method == "HEAD");
+    }
+    match resolve_request_path(site_root, base, target) {
+        Some(path) => {
+            let body = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+            respond(&mut stream, "200 OK", content_type(&path), &body, method == "HEAD")
+        }
+        None => respond(&mut stream, "404 Not Found", "text/plain; charset=utf-8", b"not found\n", method == "HEAD"),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8], head_only: bool) -> Result<()> {
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(header.as_bytes())?;
+    if !head_only {
+        stream.write_all(body)?;
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+/// Map a request target onto a file under `site_root`, mounted at `base` (see `--base-path`).
+/// Directory requests resolve to their `index.html`. Anything that would leave the site root is rejected.
+fn resolve_request_path(site_root: &Path, base: &str, target: &str) -> Option<PathBuf> {
+    let path_part = target.split(['?', '#']).next().unwrap_or("/");
+    let path_part = path_part.strip_prefix(base)?;
+    if !path_part.is_empty() && !path_part.starts_with('/') {
+        return None;
+    }
+    let decoded = percent_decode_str(path_part).decode_utf8().ok()?;
+    let mut path = site_root.to_path_buf();
+    for comp in Path::new(decoded.trim_start_matches('/')).components() {
+        match comp {
+            Component::Normal(seg) => path.push(seg),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    if path.is_file() { Some(path) } else { None }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "md" | "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn unique_site() -> PathBuf {
+        let mut p = std::env::temp_dir();
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        p.push(format!("prov-serve-test-{}", nanos));
+        fs::create_dir_all(p.join("a").join("x")).unwrap();
+        fs::write(p.join("index.html"), "home").unwrap();
+        fs::write(p.join("a").join("x").join("index.html"), "x").unwrap();
+        p
+    }
+
+    #[test]
+    fn resolves_directories_to_index_html() {
+        let site = unique_site();
+        assert_eq!(resolve_request_path(&site, "", "/"), Some(site.join("index.html")));
+        assert_eq!(resolve_request_path(&site, "", "/a/x/?q=1"), Some(site.join("a").join("x").join("index.html")));
+        assert_eq!(resolve_request_path(&site, "", "/missing"), None);
+    }
+
+    #[test]
+    fn rejects_paths_escaping_site_root() {
+        let site = unique_site();
+        assert_eq!(resolve_request_path(&site, "", "/../index.html"), None);
+        assert_eq!(resolve_request_path(&site, "", "/a/%2e%2e/%2e%2e/index.html"), None);
+    }
+
+    #[test]
+    fn strips_base_path_prefix() {
+        let site = unique_site();
+        assert_eq!(resolve_request_path(&site, "/proj/abc", "/proj/abc/a/x/"), Some(site.join("a").join("x").join("index.html")));
+        assert_eq!(resolve_request_path(&site, "/proj/abc", "/proj/abcdef/"), None);
+        assert_eq!(resolve_request_path(&site, "/proj/abc", "/a/x/"), None);
+    }
+
+    #[test]
+    fn watches_page_sources() {
+        use crate::ssg::test_support::*;
+        let root = example_copy();
+        edit_manifest(&root, |manifest| {
+            manifest["pages"] = serde_json::json!([{"id": "spec", "title": "Spec", "markup": "ci/pages/spec.pml"}]);
+        });
+        let files = watched_files(&test_args(root.clone(), unique_out()));
+        assert!(files.contains(&root.join("ci/pages/spec.pml")), "{:?}", files);
+    }
+}
@@ -1,20 +1,30 @@
-/// THIS FILE IS GETTING TOO BIG
-/// PLEASE MODULARIZE AT YOUR NEXT REFACTORING
+// The `provenance-ssg` command line and the site build: manifest verification, assets, artifact
+// pages and badges. Proofdown pages, evidence coverage, search indexes and feeds have their own
+// modules and write through `Site`.
 
 use anyhow::{anyhow, Context, Result};
 use badges as badges_lib;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use manifest_contract as mc;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC, AsciiSet};
-#[cfg(feature = "external_pml")]
-use proofdown_parser as pml;
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 
+use crate::coverage;
+use crate::feeds;
+use crate::interpolate::Vars;
+use crate::pages;
+use crate::proofdown;
+use crate::pml_render::Citations;
 use crate::render;
+use crate::search;
+use crate::theme;
+use crate::bundle::{BundleArgs, VerifyBundleArgs};
+use crate::export::ExportHtmlArgs;
+use crate::serve::ServeArgs;
 use frontend as fe;
 
 // Do NOT percent-encode common safe filename characters to avoid static servers decoding
@@ -23,6 +33,28 @@ const SAFE_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'.').remove(b'-').r
 
 #[derive(Parser, Debug, Clone)]
 #[command(name = "provenance-ssg", version, about = "Static site generator for Provenance (read-only)")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(flatten)]
+    pub build: Args,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Build, then serve the site on localhost and rebuild when inputs change
+    Serve(ServeArgs),
+    /// Export a single self-contained HTML file with the verification report and all artifacts
+    ExportHtml(ExportHtmlArgs),
+    /// Build the site and pack it with manifest, signature and artifacts into a deterministic tar
+    Bundle(BundleArgs),
+    /// Re-verify an evidence bundle from the archive alone
+    VerifyBundle(VerifyBundleArgs),
+}
+
+#[derive(clap::Args, Debug, Clone)]
 pub struct Args {
     /// Project root directory (where CI artifacts and .provenance live)
     #[arg(long, default_value = ".")]
@@ -55,11 +87,210 @@ pub struct Args {
     /// Maximum inline bytes before truncation notice (for JSON/markdown)
     #[arg(long, default_value_t = 1_000_000usize)]
     pub truncate_inline_bytes: usize,
+
+    /// URL path prefix the site is hosted under (e.g. /project/commit); empty for the host root
+    #[arg(long, default_value = "")]
+    pub base_path: String,
+
+    /// Public origin of the site (e.g. https://evidence.example.com); enables sitemap.xml,
+    /// feed.xml and absolute OpenGraph URLs
+    #[arg(long)]
+    pub site_url: Option<String>,
+
+    /// Theme file (JSON with colors, dark_colors, fonts and spacing tokens) for the site stylesheet
+    #[arg(long)]
+    pub theme: Option<PathBuf>,
+
+    /// Maximum nesting of Proofdown `<include.pml>` components
+    #[arg(long, default_value_t = 3usize)]
+    pub max_include_depth: usize,
+
+    /// Fail when an artifact is not referenced by any Proofdown page (otherwise this is only
+    /// reported), or when no page was rendered from Proofdown to check
+    #[arg(long, default_value_t = false)]
+    pub strict_evidence_coverage: bool,
+}
+
+impl Default for Args {
+    /// The command-line defaults, as if no flag were given.
+    fn default() -> Self {
+        Cli::parse_from(["provenance-ssg"]).build
+    }
 }
 
 pub fn run_with_args(args: Args) -> Result<()> {
     fs::create_dir_all(&args.out).context("create output dir")?;
+    let base = normalize_base_path(&args.base_path);
+
+    let Verified { manifest, manifest_json, provenance_verified, mut views, kpis, vars, title } = load_verified(&args)?;
 
+    // Head metadata; absolute URLs only when the public origin is known
+    let site_url = args.site_url.as_deref().and_then(feeds::normalize_site_url);
+    // The feed needs a build time; without one it is skipped (and not advertised)
+    let feed_epoch = site_url.as_ref().and_then(|_| feeds::build_epoch(&args.root, &manifest.commit));
+    if site_url.is_some() && feed_epoch.is_none() {
+        eprintln!("feed.xml skipped: no build time (set SOURCE_DATE_EPOCH or build from a git checkout containing commit {})", manifest.commit);
+    }
+
+    // Authored pages (manifest `pages`) in navigation order, with interpolated titles
+    let mut pages = manifest.pages.iter().map(|p| Ok((p, vars.render(&p.title)?))).collect::<Result<Vec<_>>>()?;
+    pages.sort_by(|(a, _), (b, _)| (a.nav_order, &a.id).cmp(&(b.nav_order, &b.id)));
+    let site = Site {
+        args: &args,
+        base: base.clone(),
+        site_url,
+        feed: feed_epoch.is_some(),
+        title: title.clone(),
+        at_commit: format!("{}@{}", manifest.repo, manifest.commit),
+        pages,
+    };
+
+    // Prepare assets dir (always present to host site-wide assets like CSS)
+    let assets_dir = args.out.join("assets");
+    fs::create_dir_all(&assets_dir).context("create assets dir")?;
+    // Write site CSS (extracted from inline styles)
+    fs::write(assets_dir.join("site.css"), theme::site_css(args.theme.as_deref())?).context("write site.css")?;
+    fs::write(assets_dir.join("search.js"), render::search_js()).context("write search.js")?;
+    fs::write(assets_dir.join("fulltext.js"), render::fulltext_js()).context("write fulltext.js")?;
+
+    // Copy artifact assets if requested and point downloads at them
+    for v in &mut views {
+        let a = &v.artifact;
+        let src = args.root.join(&a.path);
+        v.download_href = if args.copy_assets && src.is_file() {
+            let file_name = src
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("artifact");
+            let safe_name = sanitize_file_name(file_name);
+            let dest_sub = PathBuf::from("assets").join(&a.id);
+            let dest_dir = args.out.join(&dest_sub);
+            fs::create_dir_all(&dest_dir).ok();
+            let dest_path = dest_dir.join(&safe_name);
+            let _ = fs::copy(&src, &dest_path);
+            format!("{}/{}/{}", base, dest_sub.to_string_lossy(), safe_name)
+        } else {
+            format!("{}/{}", base, a.path)
+        };
+    }
+
+    // Index page and Proofdown pages, then the coverage of what they cite
+    let citations = Citations::default();
+    let rendered = pages::publish(&site, &manifest, &views, &vars, &kpis, &citations)?;
+    coverage::check(&args, &manifest, &views, &citations, rendered)?;
+
+    // Per-artifact pages
+    let evidence = evidence_links(&views, |id| format!("{}/a/{}/", base, id));
+    for v in &views {
+        let a = &v.artifact;
+        let body = render_artifact_body(v, &args.root, args.truncate_inline_bytes, &evidence)?;
+        let details = fe::ArtifactDetails {
+            expected_sha256: a.sha256.clone(),
+            computed_sha256: v.digest_hex.clone(),
+            size_bytes: fs::metadata(args.root.join(&a.path)).ok().map(|m| m.len()),
+            source_path: a.path.clone(),
+            commit: manifest.commit.clone(),
+            workflow_run_id: json_scalar(&manifest.workflow_run.id),
+            workflow_run_url: manifest.workflow_run.url.clone(),
+            workflow_run_attempt: manifest.workflow_run.attempt,
+            verify_commands: verify_commands(&args, &a.id),
+        };
+        site.write_page(
+            &format!("/a/{}/", a.id),
+            format!("{} — {}", a.title, title),
+            format!("{} ({}) from {}: {}", a.title, a.render, site.at_commit, if v.verified { "verified" } else { "digest mismatch" }),
+            fe::render_artifact(&base, &v.summary(), &details, &body),
+        )?;
+    }
+
+    // Artifact listings, search indexes and discovery files
+    search::publish(&site, &views)?;
+    feeds::publish(&site, &manifest, &manifest_json, &views, &kpis, feed_epoch)?;
+
+    // badges
+    let badge_dir = args.out.join("badge");
+    fs::create_dir_all(&badge_dir).ok();
+    let all_artifacts_verified = views.iter().all(|v| v.verified);
+    let prov_badge = badges_lib::badge_provenance(provenance_verified && all_artifacts_verified);
+    write_badge(&badge_dir, "provenance", &prov_badge)?;
+    for r in render::REGISTRY {
+        let bytes = load_artifact_bytes(&manifest.artifacts, &args.root, r.hint())?;
+        if let Some((name, b)) = r.badge(bytes.as_deref()) {
+            write_badge(&badge_dir, name, &b)?;
+        }
+    }
+
+    println!("Site generated at {}", args.out.display());
+    Ok(())
+}
+
+/// What every page of a build shares: where it goes, how links are formed, head metadata and
+/// navigation.
+pub(crate) struct Site<'a> {
+    pub args: &'a Args,
+    /// Normalized `--base-path` ("" or "/seg")
+    pub base: String,
+    /// Normalized `--site-url`; absolute URLs are only formed when it is set
+    pub site_url: Option<String>,
+    /// Whether `feed.xml` is written (and advertised in page heads)
+    pub feed: bool,
+    /// `front_page.title` with placeholders resolved
+    pub title: String,
+    /// `{repo}@{commit}`, for page descriptions
+    pub at_commit: String,
+    /// Authored pages (manifest `pages`) in navigation order, with interpolated titles
+    pub pages: Vec<(&'a mc::Page, String)>,
+}
+
+impl Site<'_> {
+    /// Absolute URL of a site path such as "/a/coverage/".
+    pub(crate) fn abs(&self, path: &str) -> Option<String> {
+        self.site_url.as_ref().map(|origin| format!("{}{}{}", origin, self.base, path))
+    }
+
+    fn nav(&self, path: &str) -> Vec<fe::NavLink> {
+        self.pages
+            .iter()
+            .map(|(p, label)| {
+                let page_path = format!("/p/{}/", p.id);
+                fe::NavLink { label: label.clone(), current: page_path == path, href: format!("{}{}", self.base, page_path) }
+            })
+            .collect()
+    }
+
+    /// Wrap `inner` in the site layout and write it to `{out}{path}index.html`; `path` is a site
+    /// path such as "/" or "/p/spec/".
+    pub(crate) fn write_page(&self, path: &str, title: String, description: String, inner: String) -> Result<()> {
+        let meta = fe::PageMeta {
+            title,
+            description,
+            url: self.abs(path),
+            image: self.abs("/badge/provenance.svg"),
+            feed: if self.feed { self.abs("/feed.xml") } else { None },
+        };
+        let html = fe::render_document(&self.base, &meta, &self.nav(path), inner);
+        let dir = self.args.out.join(path.trim_matches('/'));
+        fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        write_html(dir.join("index.html"), &html)
+    }
+}
+
+/// Manifest and artifacts after schema/semantic checks, optional signature verification and
+/// per-artifact digest checks. Shared by the site build and the exporters.
+pub(crate) struct Verified {
+    pub manifest: mc::Manifest,
+    pub manifest_json: serde_json::Value,
+    pub provenance_verified: bool,
+    /// Sorted by artifact id; `download_href` is left empty for the caller to fill in.
+    pub views: Vec<ArtifactViewExt>,
+    pub kpis: BTreeMap<&'static str, String>,
+    /// Placeholder values for titles and Proofdown text (see `interpolate.rs`)
+    pub vars: Vars,
+    /// `front_page.title` with placeholders resolved
+    pub title: String,
+}
+
+pub(crate) fn load_verified(args: &Args) -> Result<Verified> {
     // Load manifest (typed + raw JSON)
     let manifest_path = args.root.join(&args.manifest);
     let (manifest, manifest_json) = mc::load_manifest(&manifest_path)?;
@@ -67,6 +298,7 @@ pub fn run_with_args(args: Args) -> Result<()> {
     // Schema + semantics
     let schema_path = args
         .schema_path
+        .clone()
         .unwrap_or_else(|| PathBuf::from("schemas/manifest.schema.json"));
     let schema_text = fs::read_to_string(&schema_path)
         .with_context(|| format!("read schema at {}", schema_path.display()))?;
@@ -91,184 +323,93 @@ pub fn run_with_args(args: Args) -> Result<()> {
         }
     }
 
-    // Prepare assets dir (always present to host site-wide assets like CSS)
-    let assets_dir = args.out.join("assets");
-    fs::create_dir_all(&assets_dir).context("create assets dir")?;
-    // Write site CSS (extracted from inline styles)
-    fs::write(assets_dir.join("site.css"), render::site_css()).context("write site.css")?;
-
     // Build artifact views (deterministic order)
     let mut views = Vec::new();
     for a in &manifest.artifacts {
         let src = args.root.join(&a.path);
         let (verified, digest_hex) = verify_sha256(&src, &a.sha256).unwrap_or((false, None));
-
-        // Copy artifact asset if requested
-        let download_href = if args.copy_assets && src.is_file() {
-            let base = src
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("artifact");
-            let safe_name = sanitize_file_name(base);
-            let dest_sub = PathBuf::from("assets").join(&a.id);
-            let dest_dir = args.out.join(&dest_sub);
-            fs::create_dir_all(&dest_dir).ok();
-            let dest_path = dest_dir.join(&safe_name);
-            let _ = fs::copy(&src, &dest_path);
-            format!("/{}/{}", dest_sub.to_string_lossy(), safe_name)
-        } else {
-            format!("/{}", a.path)
-        };
-
-        views.push(ArtifactViewExt::from(a.clone(), verified, download_href, digest_hex));
+        views.push(ArtifactViewExt::from(a.clone(), verified, String::new(), digest_hex));
     }
     // Sort by artifact id to guarantee stable ordering regardless of manifest input ordering
     views.sort_by(|a, b| a.artifact.id.cmp(&b.artifact.id));
 
-    // KPIs
-    let mut kpis: BTreeMap<&str, String> = BTreeMap::new();
-    let mut tests_summary: Option<render::TestSummary> = None;
-    let mut coverage: Option<render::Coverage> = None;
-    if let Some(ts) = load_artifact_bytes(&manifest.artifacts, &args.root, "summary:test")? {
-        if let Ok(s) = serde_json::from_slice::<render::TestSummary>(&ts) {
-            kpis.insert("Tests", format!("{} total, {} passed, {} failed", s.total, s.passed, s.failed));
-            kpis.insert("Duration", format!("{:.2}s", s.duration_seconds));
-            tests_summary = Some(s);
+    // KPIs from the first artifact of each render hint that provides them
+    let mut kpis: BTreeMap<&'static str, String> = BTreeMap::new();
+    for r in render::REGISTRY {
+        if let Some(bytes) = load_artifact_bytes(&manifest.artifacts, &args.root, r.hint())? {
+            kpis.extend(r.kpis(&bytes));
         }
     }
-    if let Some(cv) = load_artifact_bytes(&manifest.artifacts, &args.root, "table:coverage")? {
-        if let Ok(c) = serde_json::from_slice::<render::Coverage>(&cv) {
-            if let Some(ref t) = c.total { kpis.insert("Coverage", format!("{:.1}%", t.pct)); }
-            coverage = Some(c);
-        }
+    let tests_summary: Option<render::TestSummary> =
+        load_artifact_bytes(&manifest.artifacts, &args.root, "summary:test")?.and_then(|b| serde_json::from_slice(&b).ok());
+    let coverage: Option<render::Coverage> =
+        load_artifact_bytes(&manifest.artifacts, &args.root, "table:coverage")?.and_then(|b| serde_json::from_slice(&b).ok());
+
+    let vars = Vars::for_build(&manifest, &views, tests_summary.as_ref(), coverage.as_ref()).context("interpolate front page title")?;
+    let title = vars.get("front_page.title").unwrap_or_default().to_string();
+    // Artifact titles are resolved once here, so pages, cards, indexes and links all show the same text
+    for v in &mut views {
+        v.artifact.title = vars.render(&v.artifact.title).with_context(|| format!("interpolate title of artifact {}", v.artifact.id))?;
     }
+    Ok(Verified { manifest, manifest_json, provenance_verified, views, kpis, vars, title })
+}
 
-    // Index page
-    #[cfg(feature = "external_pml")]
-    {
-        let front_path = args.root.join(&manifest.front_page.markup);
-        let front_text = fs::read_to_string(&front_path).with_context(|| format!("read front page {}", front_path.display()))?;
-        let doc = pml::parse(&front_text).map_err(|e| anyhow!("parse Proofdown front page at {}:{}: {}", e.line, e.col, e.msg))?;
-        let index_inner = render_front_page(&doc, &manifest, &views, args.truncate_inline_bytes, &args.root)?;
-        let index_html = render::page_base(index_inner);
-        write_html(args.out.join("index.html"), &index_html)?;
-    }
-    #[cfg(not(feature = "external_pml"))]
-    {
-        // Map to frontend artifacts for RSX rendering
-        let featured: Vec<fe::Artifact> = views.iter().map(|v| fe::Artifact {
-            id: &v.artifact.id,
-            title: &v.artifact.title,
-            render: &v.artifact.render,
-            media_type: &v.artifact.media_type,
-            verified: v.verified,
-            download_href: &v.download_href,
-        }).collect();
-        let inner = fe::render_index(&manifest.front_page.title, &manifest.commit, &kpis, &featured);
-        let index_html = render::page_base(inner);
-        write_html(args.out.join("index.html"), &index_html)?;
-    }
+/// Artifacts an `llm-proof` claim may cite, keyed by id; `href_for` maps an id to its link in the output.
+pub(crate) fn evidence_links(views: &[ArtifactViewExt], href_for: impl Fn(&str) -> String) -> BTreeMap<String, render::EvidenceLink> {
+    views
+        .iter()
+        .map(|v| {
+            let link = render::EvidenceLink { title: v.artifact.title.clone(), href: href_for(&v.artifact.id), verified: v.verified };
+            (v.artifact.id.clone(), link)
+        })
+        .collect()
+}
 
-    // Per-artifact pages
-    for v in &views {
-        let a = &v.artifact;
-        let src = args.root.join(&a.path);
-        let bytes = fs::read(&src).unwrap_or_default();
-        let body = match a.render.as_str() {
-            "summary:test" => render::render_tests_summary(&bytes).unwrap_or_else(|e| format!("<pre>parse error: {}</pre>", e)),
-            "table:coverage" => render::render_coverage(&bytes).unwrap_or_else(|e| format!("<pre>parse error: {}</pre>", e)),
-            "markdown" => {
-                if file_too_large(&src, args.truncate_inline_bytes) {
-                    format!("<div class=\"card\"><strong>Truncated</strong>: file too large. <a href=\"{}\">Download</a></div>", v.download_href)
-                } else {
-                    render::render_markdown(&String::from_utf8_lossy(&bytes))
-                }
-            }
-            "json" => {
-                if file_too_large(&src, args.truncate_inline_bytes) {
-                    format!("<div class=\"card\"><strong>Truncated</strong>: file too large. <a href=\"{}\">Download</a></div>", v.download_href)
-                } else {
-                    render::render_json_pretty(&bytes).unwrap_or_else(|e| format!("<pre>parse error: {}</pre>", e))
-                }
-            }
-            "image" => render::render_image(&v.download_href, &a.title),
-            other => return Err(anyhow!("Unsupported render: {} for id {}", other, a.id)),
-        };
-        let fa = fe::Artifact {
-            id: &a.id,
-            title: &a.title,
-            render: &a.render,
-            media_type: &a.media_type,
-            verified: v.verified,
-            download_href: &v.download_href,
-        };
-        let inner = fe::render_artifact(&fa, &body);
-        let page_html = render::page_base(inner);
-        let out_dir = args.out.join("a").join(&a.id);
-        fs::create_dir_all(&out_dir).context("create artifact page dir")?;
-        write_html(out_dir.join("index.html"), &page_html)?;
-    }
-
-    // Artifacts index and search index
-    {
-        // artifacts index
-        let items: Vec<fe::Artifact> = views.iter().map(|v| fe::Artifact {
-            id: &v.artifact.id,
-            title: &v.artifact.title,
-            render: &v.artifact.render,
-            media_type: &v.artifact.media_type,
-            verified: v.verified,
-            download_href: &v.download_href,
-        }).collect();
-        let inner = fe::render_artifacts_index(&items);
-        let html = render::page_base(inner);
-        let art_dir = args.out.join("artifacts");
-        fs::create_dir_all(&art_dir).ok();
-        write_html(art_dir.join("index.html"), &html)?;
-
-        // search index
-        #[derive(serde::Serialize)]
-        struct SearchItem<'a> { id: &'a str, title: &'a str, render: &'a str, media_type: &'a str, verified: bool }
-        let sidx: Vec<SearchItem> = views.iter().map(|v| SearchItem {
-            id: &v.artifact.id,
-            title: &v.artifact.title,
-            render: &v.artifact.render,
-            media_type: &v.artifact.media_type,
-            verified: v.verified,
-        }).collect();
-        let txt = serde_json::to_string_pretty(&sidx)? + "\n";
-        fs::write(args.out.join("search_index.json"), txt).ok();
-    }
-
-    // robots.txt
-    let robots = "User-agent: *\nDisallow: /fragment/\n";
-    fs::write(args.out.join("robots.txt"), robots).ok();
+/// Render the body of an artifact page according to its `render` hint.
+pub(crate) fn render_artifact_body(v: &ArtifactViewExt, root: &Path, truncate_limit: usize, evidence: &BTreeMap<String, render::EvidenceLink>) -> Result<String> {
+    let a = &v.artifact;
+    let r = render::lookup(&a.render).ok_or_else(|| anyhow!("Unsupported render: {} for id {}", a.render, a.id))?;
+    let Some((bytes, size)) = read_for_render(r, &root.join(&a.path), truncate_limit) else {
+        return Ok(format!("<div class=\"card\"><strong>Truncated</strong>: file too large. <a href=\"{}\">Download</a></div>", v.download_href));
+    };
+    let input = render::Input {
+        id: &a.id,
+        title: &a.title,
+        href: &v.download_href,
+        media_type: &a.media_type,
+        sha256: &a.sha256,
+        verified: v.verified,
+        bytes: &bytes,
+        size,
+        evidence,
+    };
+    let body = r.render(&input).unwrap_or_else(|e| format!("<pre>parse error: {}</pre>", proofdown::esc(&e.to_string())));
+    Ok(body)
+}
 
-    // badges
-    let badge_dir = args.out.join("badge");
-    fs::create_dir_all(&badge_dir).ok();
-    let all_artifacts_verified = views.iter().all(|v| v.verified);
-    let prov_badge = badges_lib::badge_provenance(provenance_verified && all_artifacts_verified);
-    write_badge(&badge_dir, "provenance", &prov_badge)?;
-    if let Some(s) = &tests_summary {
-        let s_badge = badges_lib::TestSummary { total: s.total, passed: s.passed, failed: s.failed, duration_seconds: s.duration_seconds };
-        let b = badges_lib::badge_tests(&s_badge);
-        write_badge(&badge_dir, "tests", &b)?;
-    } else {
-        let b = badges_lib::badge_error("tests", "error");
-        write_badge(&badge_dir, "tests", &b)?;
-    }
-    if let Some(c) = &coverage {
-        let c_badge = badges_lib::Coverage { total: c.total.as_ref().map(|t| badges_lib::CoverageTotal { pct: t.pct }) };
-        let b = badges_lib::badge_coverage(&c_badge);
-        write_badge(&badge_dir, "coverage", &b)?;
-    } else {
-        let b = badges_lib::badge_error("coverage", "error");
-        write_badge(&badge_dir, "coverage", &b)?;
-    }
+/// Normalize a user-supplied base path to either "" or "/seg/seg" (leading slash, no trailing slash),
+/// so links can always be formed as `{base}/a/{id}/`.
+pub(crate) fn normalize_base_path(raw: &str) -> String {
+    let segs: Vec<&str> = raw.split('/').filter(|s| !s.is_empty()).collect();
+    if segs.is_empty() { String::new() } else { format!("/{}", segs.join("/")) }
+}
 
-    println!("Site generated at {}", args.out.display());
-    Ok(())
+/// `provenance-tools` invocations that re-check one artifact (and the signed manifest) from a checkout.
+fn verify_commands(args: &Args, artifact_id: &str) -> Vec<String> {
+    let manifest = args.manifest.to_string_lossy();
+    let pubkey = args.pubkey.as_deref().unwrap_or("<PUBKEY>");
+    vec![
+        format!("provenance-tools verify --root . --manifest {} --artifact {}", manifest, artifact_id),
+        format!("provenance-tools verify --root . --manifest {} --pubkey {} --artifact {}", manifest, pubkey, artifact_id),
+    ]
+}
+
+/// Workflow run ids may be numbers or strings in the manifest; render either without quotes.
+pub(crate) fn json_scalar(v: &serde_json::Value) -> String {
+    match v {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
 }
 
 fn write_html(path: PathBuf, html: &str) -> Result<()> {
@@ -291,26 +432,27 @@ fn verify_sha256(path: impl AsRef<Path>, hex_expected: &str) -> Result<(bool, Op
 
 fn sanitize_file_name(name: &str) -> String { utf8_percent_encode(name, SAFE_ENCODE_SET).to_string() }
 
-struct ArtifactViewExt {
-    artifact: mc::Artifact,
-    verified: bool,
-    download_href: String,
-    digest_hex: Option<String>,
+pub(crate) struct ArtifactViewExt {
+    pub artifact: mc::Artifact,
+    pub verified: bool,
+    pub download_href: String,
+    pub digest_hex: Option<String>,
 }
 
 impl ArtifactViewExt {
     fn from(artifact: mc::Artifact, verified: bool, download_href: String, digest_hex: Option<String>) -> Self {
         Self { artifact, verified, download_href, digest_hex }
     }
-    fn as_view(&self) -> render::ArtifactView<'_> {
-        render::ArtifactView {
+
+    /// The fields listings and artifact pages show.
+    pub(crate) fn summary(&self) -> fe::Artifact<'_> {
+        fe::Artifact {
             id: &self.artifact.id,
             title: &self.artifact.title,
             render: &self.artifact.render,
             media_type: &self.artifact.media_type,
-            path_rel: &self.artifact.path,
             verified: self.verified,
-            download_href: self.download_href.clone(),
+            download_href: &self.download_href,
         }
     }
 }
@@ -326,8 +468,24 @@ fn load_artifact_bytes(artifacts: &[mc::Artifact], root: &Path, render_hint: &st
     Ok(None)
 }
 
-fn file_too_large(path: &Path, limit: usize) -> bool {
-    match fs::metadata(path) { Ok(m) => m.len() as usize > limit, Err(_) => false }
+/// The part of an artifact file `r` renders, and the file's size: the whole file up to `limit` bytes,
+/// else its first `preview_bytes`. `None` when the file is too large for a renderer that inlines it.
+pub(crate) fn read_for_render(r: &dyn render::Renderer, path: &Path, limit: usize) -> Option<(Vec<u8>, u64)> {
+    let size = fs::metadata(path).map_or(0, |m| m.len());
+    if size <= limit as u64 {
+        return Some((fs::read(path).unwrap_or_default(), size));
+    }
+    match r.preview_bytes() {
+        Some(n) => {
+            let mut head = Vec::new();
+            if let Ok(f) = fs::File::open(path) {
+                f.take(n as u64).read_to_end(&mut head).ok();
+            }
+            Some((head, size))
+        }
+        None if r.inlines_file() => None,
+        None => Some((fs::read(path).unwrap_or_default(), size)),
+    }
 }
 
 fn write_badge(dir: &Path, kind: &str, b: &badges_lib::ShieldsBadge) -> Result<()> {
@@ -340,152 +498,179 @@ fn write_badge(dir: &Path, kind: &str, b: &badges_lib::ShieldsBadge) -> Result<(
     Ok(())
 }
 
-#[cfg(feature = "external_pml")]
-fn render_front_page(doc: &proofdown_ast::Document, manifest: &mc::Manifest, views: &[ArtifactViewExt], truncate_limit: usize, root: &Path) -> Result<String> {
-    // Minimal renderer: grid/card + artifact.summary/table/markdown
-    fn render_blocks(blocks: &[proofdown_ast::Block], manifest: &mc::Manifest, views: &[ArtifactViewExt], truncate_limit: usize, root: &Path) -> Result<String> {
-        let mut out = String::new();
-        for b in blocks {
-            match b {
-                proofdown_ast::Block::Heading { level, inlines } => {
-                    let txt = inlines_to_text(inlines);
-                    out.push_str(&format!("<h{}>{}</h{}>", level, html_escape(&interpolate(&txt, manifest)), level));
-                }
-                proofdown_ast::Block::Paragraph { inlines } => {
-                    let txt = inlines_to_text(inlines);
-                    out.push_str(&format!("<p>{}</p>", html_escape(&interpolate(&txt, manifest))));
-                }
-                proofdown_ast::Block::Component(c) => {
-                    out.push_str(&render_component(c, manifest, views, truncate_limit, root)?);
-                }
-                _ => {}
-            }
-        }
-        Ok(out)
+/// Fixtures shared by the unit tests of every module that builds a site.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::Args;
+    use std::path::{Path, PathBuf};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    pub(crate) fn repo_root() -> PathBuf {
+        let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        crate_dir.parent().and_then(|p| p.parent()).unwrap().to_path_buf()
     }
 
-    fn render_component(c: &proofdown_ast::Component, manifest: &mc::Manifest, views: &[ArtifactViewExt], truncate_limit: usize, root: &Path) -> Result<String> {
-        match c.name.as_str() {
-            "grid" => {
-                let cols = pml::find_attr(&c.attrs, "cols").unwrap_or("3");
-                let mut inner = String::new();
-                inner.push_str(&render_blocks(&c.children, manifest, views, truncate_limit, root)?);
-                Ok(format!("<div class=\"cards\" style=\"grid-template-columns:repeat({},{})\">{}</div>", cols, "minmax(280px,1fr)", inner))
-            }
-            "card" => {
-                let title = pml::find_attr(&c.attrs, "title").unwrap_or("");
-                let mut inner = String::new();
-                inner.push_str(&render_blocks(&c.children, manifest, views, truncate_limit, root)?);
-                Ok(format!("<div class=\"card\"><h3>{}</h3>{}</div>", html_escape(&interpolate(title, manifest)), inner))
-            }
-            n if n.starts_with("artifact.") => render_artifact_component(&n[9..], c, manifest, views, truncate_limit, root),
-            other => Err(anyhow!("unknown component: {}", other)),
-        }
+    /// The checked-in example repository (read-only).
+    pub(crate) fn example() -> PathBuf {
+        repo_root().join("examples/minimal")
     }
 
-    fn render_artifact_component(kind: &str, c: &proofdown_ast::Component, _manifest: &mc::Manifest, views: &[ArtifactViewExt], truncate_limit: usize, root: &Path) -> Result<String> {
-        let id = pml::find_attr(&c.attrs, "id").ok_or_else(|| anyhow!("artifact.* requires id attribute"))?;
-        let v = views.iter().find(|v| v.artifact.id == id).ok_or_else(|| anyhow!("unknown artifact id: {}", id))?;
-        let a = &v.artifact;
-        let src = root.join(&a.path);
-        let bytes = fs::read(&src).unwrap_or_default();
-        match kind {
-            "summary" => render::render_tests_summary(&bytes).map_err(|e| anyhow!("{}", e)),
-            "table" => render::render_coverage(&bytes).map_err(|e| anyhow!("{}", e)),
-            "json" => {
-                if file_too_large(&src, truncate_limit) { Ok(trunc(&v.download_href)) } else { render::render_json_pretty(&bytes).map_err(|e| anyhow!("{}", e)) }
-            }
-            "markdown" => {
-                if file_too_large(&src, truncate_limit) { Ok(trunc(&v.download_href)) } else { Ok(render::render_markdown(&String::from_utf8_lossy(&bytes))) }
-            }
-            "image" => Ok(render::render_image(&v.download_href, &a.title)),
-            "link" => Ok(format!("<a href=\"/a/{}/\">{}</a>", html_escape(&a.id), html_escape(&a.title))),
-            other => Err(anyhow!("unknown artifact component: {}", other)),
-        }
+    /// A fresh path under the temp dir; nothing is created.
+    pub(crate) fn unique_out() -> PathBuf {
+        let mut p = std::env::temp_dir();
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        p.push(format!("prov-ssg-test-{}", nanos));
+        p
     }
 
-    fn trunc(href: &str) -> String { format!("<div class=\"card\"><strong>Truncated</strong>: file too large. <a href=\"{}\">Download</a></div>", href) }
-    fn html_escape(s: &str) -> String { s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;") }
-    fn interpolate(t: &str, m: &mc::Manifest) -> String {
-        let mut out = t.to_string();
-        out = out.replace("{{ commit }}", &m.commit);
-        out = out.replace("{{ front_page.title }}", &m.front_page.title);
-        out
-    }
-
-    fn inlines_to_text(inlines: &[proofdown_ast::Inline]) -> String {
-        use proofdown_ast::Inline as I;
-        let mut buf = String::new();
-        for i in inlines {
-            match i {
-                I::Text { text } => buf.push_str(text),
-                I::Emph { children }
-                | I::Strong { children }
-                | I::Strikethrough { children } => {
-                    buf.push_str(&inlines_to_text(children));
-                }
-                I::Code { text } => buf.push_str(text),
-                I::SoftBreak => buf.push(' '),
-                I::HardBreak => buf.push(' '),
-                I::Link { children, .. } => buf.push_str(&inlines_to_text(children)),
-                I::Image { alt, .. } => buf.push_str(alt),
-            }
-        }
-        buf
+    /// A private copy of the example that a test may edit.
+    pub(crate) fn example_copy() -> PathBuf {
+        let root = unique_out().join("repo");
+        std::fs::create_dir_all(&root).unwrap();
+        fs_extra::dir::copy(example(), &root, &fs_extra::dir::CopyOptions::new().content_only(true)).expect("copy example");
+        root
     }
 
-    render_blocks(&doc.blocks, manifest, views, truncate_limit, root)
+    /// Rewrite the manifest of an `example_copy`.
+    pub(crate) fn edit_manifest(root: &Path, edit: impl FnOnce(&mut serde_json::Value)) {
+        let manifest_path = root.join(".provenance/manifest.json");
+        let mut manifest: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        edit(&mut manifest);
+        std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest).unwrap()).unwrap();
+    }
+
+    /// Make `ast` the front page of an `example_copy`, as the verified artifact `front-page-ast`.
+    pub(crate) fn set_front_page_ast(root: &Path, ast: &[u8]) {
+        use sha2::{Digest, Sha256};
+        std::fs::write(root.join("ci/front_page.ast.json"), ast).unwrap();
+        edit_manifest(root, |manifest| {
+            let artifacts = manifest["artifacts"].as_array_mut().unwrap();
+            artifacts.retain(|a| a["id"] != "front-page-ast");
+            artifacts.push(serde_json::json!({
+                "id": "front-page-ast", "title": "Front page (AST)", "path": "ci/front_page.ast.json",
+                "media_type": "application/json", "render": "proofdown:ast", "sha256": format!("{:x}", Sha256::digest(ast))
+            }));
+            manifest["front_page"]["ast"] = serde_json::json!("front-page-ast");
+        });
+    }
+
+    /// Defaults plus the repository schema; override fields with `Args { .., ..test_args(root, out) }`.
+    pub(crate) fn test_args(root: PathBuf, out: PathBuf) -> Args {
+        Args { root, out, schema_path: Some(repo_root().join("schemas/manifest.schema.json")), ..Default::default() }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::test_support::*;
     use super::*;
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    fn unique_out() -> PathBuf {
-        let mut p = std::env::temp_dir();
-        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
-        p.push(format!("prov-ssg-test-{}", nanos));
-        p
-    }
+    use crate::fulltext;
 
     #[test]
     fn generates_site_minimal() {
         let out = unique_out();
-        let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        let repo_root = crate_dir.parent().and_then(|p| p.parent()).unwrap().to_path_buf();
-        let args = Args {
-            root: repo_root.join("examples/minimal"),
-            manifest: PathBuf::from(".provenance/manifest.json"),
-            out: out.clone(),
-            copy_assets: true,
-            verify_manifest: false,
-            pubkey: None,
-            schema_path: Some(repo_root.join("schemas/manifest.schema.json")),
-            truncate_inline_bytes: 1_000_000,
-        };
+        let args = test_args(example(), out.clone());
         run_with_args(args).expect("site generation succeeds");
-        assert!(out.join("index.html").is_file());
+        let index = std::fs::read_to_string(out.join("index.html")).expect("read index");
+        assert!(index.contains("<title>QA Evidence for deadbeef</title>"), "front page title is interpolated");
+        assert!(!index.contains("{{"));
         assert!(out.join("a").join("tests-summary").join("index.html").is_file());
+        assert!(out.join("assets").join("search.js").is_file());
+        let coverage = std::fs::read_to_string(out.join("a").join("coverage").join("index.html")).expect("read coverage page");
+        assert!(coverage.contains("<dt>Expected SHA-256</dt>"));
+        assert!(coverage.contains("href=\"https://github.com/acme/provenance/actions/runs/1\""));
+        assert!(coverage.contains("provenance-tools verify --root . --manifest .provenance/manifest.json --artifact coverage"));
+        let artifacts = std::fs::read_to_string(out.join("artifacts").join("index.html")).expect("read artifacts index");
+        assert!(artifacts.contains("<script src=\"/assets/search.js\" defer=\"true\"></script>"));
+        assert!(artifacts.contains("data-index=\"/search_index.json\""));
+        assert!(artifacts.contains("href=\"/artifacts/kind/summary-test/\""));
+        let listing = std::fs::read_to_string(out.join("artifacts").join("kind").join("summary-test").join("index.html")).expect("read kind listing");
+        assert!(listing.contains("href=\"/a/tests-summary/\""));
+        assert!(!listing.contains("href=\"/a/coverage/\""));
+        assert!(out.join("artifacts").join("media").join("application-json").join("index.html").is_file());
+        assert!(out.join("search").join("index.html").is_file());
+        let shard = std::fs::read_to_string(out.join("search").join(format!("shard-{:02x}.json", fulltext::shard_of("lib")))).expect("read shard");
+        assert!(shard.contains("\"lib\":[["), "coverage file paths are indexed");
     }
 
     #[test]
     fn truncates_inline_when_limit_small() {
         let out = unique_out();
-        let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        let repo_root = crate_dir.parent().and_then(|p| p.parent()).unwrap().to_path_buf();
         let args = Args {
-            root: repo_root.join("examples/minimal"),
-            manifest: PathBuf::from(".provenance/manifest.json"),
-            out: out.clone(),
-            copy_assets: true,
-            verify_manifest: false,
-            pubkey: None,
-            schema_path: Some(repo_root.join("schemas/manifest.schema.json")),
             truncate_inline_bytes: 1, // force truncation for markdown/json
+            ..test_args(example(), out.clone())
         };
         run_with_args(args).expect("site generation succeeds");
         let failures_html = std::fs::read_to_string(out.join("a").join("failures").join("index.html")).expect("read failures page");
         assert!(failures_html.contains("Truncated"));
     }
+
+    #[test]
+    fn previews_raw_binaries_over_the_limit() {
+        use sha2::{Digest, Sha256};
+        let root = example_copy();
+        let core: Vec<u8> = (0u8..=255).cycle().take(1000).collect();
+        std::fs::write(root.join("ci/core.bin"), &core).unwrap();
+        edit_manifest(&root, |manifest| {
+            manifest["artifacts"].as_array_mut().unwrap().push(serde_json::json!({
+                "id": "core", "title": "Core dump", "path": "ci/core.bin",
+                "media_type": "application/octet-stream", "render": "raw", "sha256": format!("{:x}", Sha256::digest(&core))
+            }));
+        });
+        let out = unique_out();
+        run_with_args(Args { truncate_inline_bytes: 16, ..test_args(root, out.clone()) }).expect("site generation succeeds");
+        let page = std::fs::read_to_string(out.join("a").join("core").join("index.html")).expect("read core page");
+        assert!(!page.contains("Truncated"), "{}", page);
+        assert!(page.contains("Binary data: first 512 of 1000 bytes"), "{}", page);
+        assert!(page.contains("000001f0  f0 f1 f2 f3"), "{}", page);
+    }
+
+    #[test]
+    fn interpolates_artifact_titles() {
+        let root = example_copy();
+        edit_manifest(&root, |manifest| {
+            manifest["artifacts"].as_array_mut().unwrap().iter_mut().find(|a| a["id"] == "coverage").unwrap()["title"] = "Coverage at {{ short_commit }}".into();
+        });
+        let out = unique_out();
+        run_with_args(test_args(root.clone(), out.clone())).expect("site generation succeeds");
+        let page = std::fs::read_to_string(out.join("a").join("coverage").join("index.html")).expect("read coverage page");
+        assert!(page.contains("<h1>Coverage at deadbee</h1>"), "{}", page);
+        let artifacts = std::fs::read_to_string(out.join("artifacts").join("index.html")).expect("read artifacts index");
+        assert!(artifacts.contains("<td>Coverage at deadbee</td>"), "{}", artifacts);
+        assert!(std::fs::read_to_string(out.join("search_index.json")).unwrap().contains("\"title\": \"Coverage at deadbee\""));
+
+        edit_manifest(&root, |manifest| {
+            manifest["artifacts"].as_array_mut().unwrap().iter_mut().find(|a| a["id"] == "coverage").unwrap()["title"] = "Coverage {{ nope }}".into();
+        });
+        let err = format!("{:#}", run_with_args(test_args(root, unique_out())).unwrap_err());
+        assert!(err.contains("interpolate title of artifact coverage"), "{}", err);
+    }
+
+    #[test]
+    fn prefixes_links_with_base_path() {
+        let out = unique_out();
+        let args = Args {
+            base_path: "proj/deadbeef/".into(),
+            ..test_args(example(), out.clone())
+        };
+        run_with_args(args).expect("site generation succeeds");
+        let index = std::fs::read_to_string(out.join("index.html")).expect("read index");
+        assert!(index.contains("href=\"/proj/deadbeef/assets/site.css\""));
+        assert!(index.contains("href=\"/proj/deadbeef/a/coverage/\""));
+        assert!(!index.contains("href=\"/a/"));
+    }
+
+    #[test]
+    fn applies_theme_tokens_to_site_css() {
+        let out = unique_out();
+        let args = Args {
+            theme: Some(repo_root().join("examples/themes/acme.json")),
+            ..test_args(example(), out.clone())
+        };
+        run_with_args(args).expect("site generation succeeds");
+        let css = std::fs::read_to_string(out.join("assets").join("site.css")).expect("read css");
+        assert!(css.contains(":root{--color-bg:#ffffff;"));
+        assert!(css.contains("@media (prefers-color-scheme: dark){:root{--color-bg:#0b0d12;--color-link:#7aa7ff;}}"));
+        assert!(css.contains("--space-container:1200px;"));
+    }
 }
@@ -0,0 +1,120 @@
+// Site themes. A theme file may only set design tokens (colors, fonts, spacing) that the built-in
+// stylesheet already uses; it cannot add selectors or arbitrary CSS, so a branded site keeps the
+// same structure, contrast of status badges and accessibility affordances.
+//
+// {
+//   "colors":      { "bg": "#ffffff", "text": "#1a1a1a", "link": "#0b5fff" },
+//   "dark_colors": { "bg": "#0b0d12" },
+//   "fonts":       { "body": "Inter, system-ui, sans-serif" },
+//   "spacing":     { "radius": "4px", "container": "1200px" }
+// }
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::render;
+
+const COLOR_TOKENS: &[&str] = &[
+    "bg", "text", "muted", "subtle", "link", "border", "border-strong", "surface", "surface-alt", "surface-hover", "badge-text", "ok", "warn", "err",
+];
+const FONT_TOKENS: &[&str] = &["body", "mono"];
+const SPACING_TOKENS: &[&str] = &["gap", "radius", "radius-sm", "container"];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Theme {
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+    #[serde(default)]
+    pub dark_colors: BTreeMap<String, String>,
+    #[serde(default)]
+    pub fonts: BTreeMap<String, String>,
+    #[serde(default)]
+    pub spacing: BTreeMap<String, String>,
+}
+
+/// Stylesheet for `assets/site.css`: the built-in themes, plus `--theme` overrides when given.
+pub(crate) fn site_css(theme_path: Option<&Path>) -> Result<String> {
+    match theme_path {
+        None => Ok(render::site_css()),
+        Some(p) => {
+            let theme = load_theme(p)?;
+            let (light, dark) = theme.declarations().with_context(|| format!("invalid theme {}", p.display()))?;
+            Ok(render::themed_site_css(&light, &dark))
+        }
+    }
+}
+
+pub(crate) fn load_theme(path: &Path) -> Result<Theme> {
+    let txt = fs::read_to_string(path).with_context(|| format!("read theme {}", path.display()))?;
+    serde_json::from_str(&txt).with_context(|| format!("parse theme {}", path.display()))
+}
+
+impl Theme {
+    /// Validated custom property declarations: (light + fonts + spacing, dark colors).
+    pub(crate) fn declarations(&self) -> Result<(String, String)> {
+        let color = Regex::new(r"^#(?:[0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})$").unwrap();
+        let family = r#"(?:[A-Za-z0-9_ -]+|"[A-Za-z0-9_ -]+"|'[A-Za-z0-9_ -]+')"#;
+        let font = Regex::new(&format!(r"^\s*{f}(?:\s*,\s*{f})*\s*$", f = family)).unwrap();
+        let length = Regex::new(r"^(?:0|[0-9]{1,4}(?:\.[0-9]{1,3})?(?:px|rem|em))$").unwrap();
+
+        let mut light = String::new();
+        let mut dark = String::new();
+        push_tokens(&mut light, "colors", "color", COLOR_TOKENS, &self.colors, &color, "a hex color like #1a2b3c")?;
+        push_tokens(&mut dark, "dark_colors", "color", COLOR_TOKENS, &self.dark_colors, &color, "a hex color like #1a2b3c")?;
+        push_tokens(&mut light, "fonts", "font", FONT_TOKENS, &self.fonts, &font, "a comma-separated list of font family names")?;
+        push_tokens(&mut light, "spacing", "space", SPACING_TOKENS, &self.spacing, &length, "a length in px, rem or em")?;
+        Ok((light, dark))
+    }
+}
+
+fn push_tokens(
+    out: &mut String,
+    section: &str,
+    prefix: &str,
+    allowed: &[&str],
+    values: &BTreeMap<String, String>,
+    pattern: &Regex,
+    expected: &str,
+) -> Result<()> {
+    for (name, value) in values {
+        if !allowed.contains(&name.as_str()) {
+            bail!("unknown {} token `{}` (allowed: {})", section, name, allowed.join(", "));
+        }
+        if !pattern.is_match(value) {
+            bail!("{}.{} = {:?} is not {}", section, name, value, expected);
+        }
+        out.push_str(&format!("--{}-{}:{};", prefix, name, value.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme(json: &str) -> Theme {
+        serde_json::from_str(json).expect("theme parses")
+    }
+
+    #[test]
+    fn emits_token_declarations() {
+        let t = theme(r##"{"colors":{"bg":"#fff","link":"#0b5fff"},"dark_colors":{"bg":"#000000"},"fonts":{"body":"Inter, \"Noto Sans\", sans-serif"},"spacing":{"radius":"4px"}}"##);
+        let (light, dark) = t.declarations().expect("valid theme");
+        assert_eq!(light, "--color-bg:#fff;--color-link:#0b5fff;--font-body:Inter, \"Noto Sans\", sans-serif;--space-radius:4px;");
+        assert_eq!(dark, "--color-bg:#000000;");
+    }
+
+    #[test]
+    fn rejects_anything_but_tokens() {
+        assert!(theme(r#"{"colors":{"bg":"red;} body{display:none"}}"#).declarations().is_err());
+        assert!(theme(r##"{"colors":{"background-image":"#fff"}}"##).declarations().is_err());
+        assert!(theme(r#"{"fonts":{"body":"x}</style><script>"}}"#).declarations().is_err());
+        assert!(theme(r#"{"spacing":{"gap":"calc(100vw)"}}"#).declarations().is_err());
+        assert!(serde_json::from_str::<Theme>(r#"{"css":"body{}"}"#).is_err());
+    }
+}
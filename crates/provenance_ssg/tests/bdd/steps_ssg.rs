@@ -160,11 +160,11 @@ fn run_ssg(state: &mut State, out_label: &str, limit: Option<usize>, verify: boo
         root: root,
         manifest: manifest.strip_prefix(&repo_root(state)).unwrap_or(&manifest).to_path_buf(),
         out: out.clone(),
-        copy_assets: true,
         verify_manifest: verify || state.get("verify_manifest").unwrap_or_default() == "true",
         pubkey: state.get("pubkey_b64"),
         schema_path: Some(schema),
         truncate_inline_bytes: limit.unwrap_or(1_000_000),
+        ..Default::default()
     };
     provenance_ssg::run_with_args(args)?;
     state.set("out_dir", out.to_string_lossy().to_string());
@@ -13,13 +13,9 @@ fn deterministic_output_for_index_and_pages() {
     // Build args
     let args = provenance_ssg::Args {
         root: repo_root.join("examples/minimal"),
-        manifest: PathBuf::from(".provenance/manifest.json"),
         out: out1.clone(),
-        copy_assets: true,
-        verify_manifest: false,
-        pubkey: None,
         schema_path: Some(repo_root.join("schemas/manifest.schema.json")),
-        truncate_inline_bytes: 1_000_000,
+        ..Default::default()
     };
 
     // Generate first
@@ -129,11 +129,8 @@ pub async fn when_build_twice(world: &mut World) {
         root: root.clone(),
         manifest: manifest1.strip_prefix(&root).unwrap_or(&manifest1).to_path_buf(),
         out: out1.clone(),
-        copy_assets: true,
-        verify_manifest: false,
-        pubkey: None,
         schema_path: Some(schema1),
-        truncate_inline_bytes: 1_000_000,
+        ..Default::default()
     };
     provenance_ssg::run_with_args(args1).expect("first run ok");
 
@@ -143,11 +140,8 @@ pub async fn when_build_twice(world: &mut World) {
         root: root.clone(),
         manifest: manifest_rel,
         out: out2.clone(),
-        copy_assets: true,
-        verify_manifest: false,
-        pubkey: None,
         schema_path: Some(workspace_root().join("schemas/manifest.schema.json")),
-        truncate_inline_bytes: 1_000_000,
+        ..Default::default()
     };
     provenance_ssg::run_with_args(args2).expect("second run ok");
 
@@ -257,11 +251,11 @@ fn run_ssg(world: &mut World, out_label: &str, limit: Option<usize>, verify: boo
         root: root,
         manifest: manifest.strip_prefix(&repo_root(world)).unwrap_or(&manifest).to_path_buf(),
         out: out.clone(),
-        copy_assets: true,
         verify_manifest: verify || world.get("verify_manifest").unwrap_or_default() == "true",
         pubkey: world.get("pubkey_b64"),
         schema_path: Some(schema),
         truncate_inline_bytes: limit.unwrap_or(1_000_000),
+        ..Default::default()
     };
     provenance_ssg::run_with_args(args).expect("ssg run ok");
     world.set("out_dir", out.to_string_lossy());
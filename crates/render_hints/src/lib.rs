@@ -0,0 +1,89 @@
+// Render hints and the media types each accepts: the part of the manifest contract that both
+// `manifest_contract` (validation) and `renderers` (one `Renderer` per hint) rely on. Kept free of
+// dependencies so the contract does not pull in the renderers.
+
+/// One manifest `render` value.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hint {
+    pub name: &'static str,
+    /// Media types an artifact with this hint may declare; `type/*` matches a whole type, `*/*` any.
+    pub media_types: &'static [&'static str],
+}
+
+impl Hint {
+    /// Whether `media_type` is one of `media_types`, ignoring parameters (`; charset=utf-8`) and case.
+    pub fn accepts(&self, media_type: &str) -> bool {
+        let essence = essence(media_type);
+        self.media_types.iter().any(|m| match m.strip_suffix("/*") {
+            Some("*") => true,
+            Some(ty) => essence.split('/').next() == Some(ty),
+            None => *m == essence,
+        })
+    }
+}
+
+const JSON: &[&str] = &["application/json"];
+const TEXT_FILES: &[&str] = &["text/*", "application/json", "application/xml", "application/toml", "application/yaml"];
+
+/// Every render hint, in the order of the schema's `render` enum.
+pub static HINTS: &[Hint] = &[
+    Hint { name: "markdown", media_types: &["text/markdown", "text/plain"] },
+    Hint { name: "json", media_types: JSON },
+    Hint { name: "table:coverage", media_types: JSON },
+    Hint { name: "summary:test", media_types: JSON },
+    Hint { name: "image", media_types: &["image/*"] },
+    Hint { name: "repo:file", media_types: TEXT_FILES },
+    // What `repo:` links can list: tar, optionally gzip-compressed
+    Hint { name: "repo:bundle", media_types: &["application/gzip", "application/x-gzip", "application/x-tar"] },
+    Hint { name: "repo:symbols", media_types: JSON },
+    Hint { name: "proofdown", media_types: &["text/*"] },
+    Hint { name: "proofdown:ast", media_types: JSON },
+    Hint { name: "llm-proof", media_types: JSON },
+    Hint { name: "text", media_types: &["text/*", "application/json", "application/xml", "application/toml", "application/yaml", "application/x-ndjson"] },
+    Hint { name: "raw", media_types: &["*/*"] },
+];
+
+/// The hint named `name`.
+pub fn lookup(name: &str) -> Option<&'static Hint> {
+    HINTS.iter().find(|h| h.name == name)
+}
+
+/// Checks an artifact's `render` value and media type against `HINTS`.
+pub fn check_artifact(id: &str, hint: &str, media_type: &str) -> Result<&'static Hint, String> {
+    let h = lookup(hint).ok_or_else(|| format!("unknown render: {} for id {}", hint, id))?;
+    if !h.accepts(media_type) {
+        return Err(format!("render {} does not accept media type {} for id {} (expected {})", hint, media_type, id, h.media_types.join(", ")));
+    }
+    Ok(h)
+}
+
+/// `type/subtype` of a media type: parameters dropped, whitespace trimmed, lowercased.
+pub fn essence(media_type: &str) -> String {
+    media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_media_types_without_parameters() {
+        let json = lookup("json").unwrap();
+        assert!(json.accepts("application/json"));
+        assert!(json.accepts("application/json; charset=utf-8"));
+        assert!(json.accepts("Application/JSON"));
+        assert!(!json.accepts("application/jsonx"));
+        assert!(lookup("proofdown").unwrap().accepts("text/plain;charset=utf-8"));
+        assert!(lookup("raw").unwrap().accepts("video/mp4"));
+    }
+
+    #[test]
+    fn source_archives_are_tar_only() {
+        let bundle = lookup("repo:bundle").unwrap();
+        assert!(bundle.accepts("application/gzip") && bundle.accepts("application/x-tar"));
+        assert_eq!(
+            check_artifact("src", "repo:bundle", "application/zip").unwrap_err(),
+            "render repo:bundle does not accept media type application/zip for id src (expected application/gzip, application/x-gzip, application/x-tar)"
+        );
+    }
+}
@@ -1,6 +1,11 @@
 use serde::Deserialize;
 use ammonia::Builder as HtmlSanitizer;
 
+mod registry;
+mod text;
+pub use registry::{check_artifact, lookup, Input, Renderer, REGISTRY};
+pub use text::{decode_text, render_hex_dump, render_metadata_card, render_text, HEX_PREVIEW_BYTES};
+
 #[derive(Debug, Deserialize)]
 pub struct TestSummary {
     pub total: u64,
@@ -75,3 +80,146 @@ pub fn render_coverage(json_bytes: &[u8]) -> anyhow::Result<String> {
     );
     Ok(html)
 }
+
+/// AI-authored proof (`render: llm-proof`): claims, each backed by manifest artifacts and named tests.
+///
+/// ```json
+/// { "title": "Login hardening", "claims": [
+///   { "id": "lockout", "statement": "Accounts lock after 5 failed attempts", "status": "proven",
+///     "evidence": ["tests-summary", "failures"], "tests": ["auth::lockout_after_five"] } ] }
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LlmProof {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    pub claims: Vec<Claim>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Claim {
+    pub id: String,
+    pub statement: String,
+    pub status: ClaimStatus,
+    /// Artifact ids from the manifest
+    #[serde(default)]
+    pub evidence: Vec<String>,
+    /// Test names as they appear in the test report
+    #[serde(default)]
+    pub tests: Vec<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaimStatus {
+    Proven,
+    Partial,
+    Refuted,
+    Unverified,
+}
+
+impl ClaimStatus {
+    fn label_and_class(self) -> (&'static str, &'static str) {
+        match self {
+            ClaimStatus::Proven => ("proven", "ok"),
+            ClaimStatus::Partial => ("partial", "warn"),
+            ClaimStatus::Refuted => ("refuted", "err"),
+            ClaimStatus::Unverified => ("unverified", "warn"),
+        }
+    }
+}
+
+/// A manifest artifact a claim may cite, as resolved by the caller.
+#[derive(Debug, Clone)]
+pub struct EvidenceLink {
+    pub title: String,
+    pub href: String,
+    /// Digest matched the manifest
+    pub verified: bool,
+}
+
+/// Claims in order with their evidence. A claim is flagged when it cites an artifact that is not in
+/// `evidence` or failed digest verification, or when it is marked proven without any evidence or tests.
+pub fn render_llm_proof(json_bytes: &[u8], evidence: &std::collections::BTreeMap<String, EvidenceLink>) -> anyhow::Result<String> {
+    let proof: LlmProof = serde_json::from_slice(json_bytes)?;
+    let attr = |s: &str| html_escape(s).replace('"', "&quot;");
+    let mut flagged = 0;
+    let mut items = String::new();
+    for c in &proof.claims {
+        let (label, class) = c.status.label_and_class();
+        let mut problems = Vec::new();
+        let mut ev = String::new();
+        for id in &c.evidence {
+            match evidence.get(id) {
+                Some(e) if e.verified => ev.push_str(&format!(
+                    "<li><a href=\"{}\">{}</a> <span class=\"badge ok\">verified</span></li>",
+                    attr(&e.href),
+                    html_escape(&e.title)
+                )),
+                Some(e) => {
+                    problems.push(format!("{} failed digest verification", id));
+                    ev.push_str(&format!(
+                        "<li><a href=\"{}\">{}</a> <span class=\"badge err\">digest mismatch</span></li>",
+                        attr(&e.href),
+                        html_escape(&e.title)
+                    ));
+                }
+                None => {
+                    problems.push(format!("{} is not in the manifest", id));
+                    ev.push_str(&format!("<li><code>{}</code> <span class=\"badge err\">missing</span></li>", html_escape(id)));
+                }
+            }
+        }
+        if c.status == ClaimStatus::Proven && c.evidence.is_empty() && c.tests.is_empty() {
+            problems.push("marked proven without evidence or tests".to_string());
+        }
+        if !problems.is_empty() {
+            flagged += 1;
+        }
+
+        items.push_str(&format!(
+            "<li class=\"claim{}\" id=\"claim-{}\"><p><span class=\"badge {}\">{}</span> <strong>{}</strong></p>",
+            if problems.is_empty() { "" } else { " flagged" },
+            attr(&c.id),
+            class,
+            label,
+            html_escape(&c.statement)
+        ));
+        if !problems.is_empty() {
+            items.push_str(&format!("<p class=\"claim-flag\"><strong>Flagged:</strong> {}</p>", html_escape(&problems.join("; "))));
+        }
+        if !ev.is_empty() {
+            items.push_str(&format!("<h4>Evidence</h4><ul>{}</ul>", ev));
+        }
+        if !c.tests.is_empty() {
+            let tests: String = c.tests.iter().map(|t| format!("<li><code>{}</code></li>", html_escape(t))).collect();
+            items.push_str(&format!("<h4>Tests</h4><ul>{}</ul>", tests));
+        }
+        if let Some(notes) = &c.notes {
+            items.push_str(&format!("<p class=\"muted\">{}</p>", html_escape(notes)));
+        }
+        items.push_str("</li>");
+    }
+
+    let mut html = String::from("<div class=\"llm-proof\">");
+    if let Some(t) = &proof.title {
+        html.push_str(&format!("<h3>{}</h3>", html_escape(t)));
+    }
+    if let Some(s) = &proof.summary {
+        html.push_str(&format!("<p>{}</p>", html_escape(s)));
+    }
+    let proven = proof.claims.iter().filter(|c| c.status == ClaimStatus::Proven).count();
+    html.push_str(&format!(
+        "<p class=\"muted\">{} claims · {} proven · {} flagged</p><ol class=\"claims\">{}</ol></div>",
+        proof.claims.len(),
+        proven,
+        flagged,
+        items
+    ));
+    Ok(html)
+}
@@ -0,0 +1,272 @@
+// Render hints as plugins. Each manifest `render` value is one `Renderer`; the artifact pages,
+// Proofdown components, KPIs and badges all look hints up here, so adding a viewer means adding one
+// implementation to `REGISTRY`, and the hint with its media types to `render_hints::HINTS` (which
+// the manifest checks use) and the schema's `render` enum.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use badges::ShieldsBadge;
+
+use crate::{html_escape, Coverage, EvidenceLink, TestSummary};
+
+/// One artifact as handed to a renderer.
+pub struct Input<'a> {
+    pub id: &'a str,
+    pub title: &'a str,
+    /// Download link for the artifact file (already URL-safe)
+    pub href: &'a str,
+    pub media_type: &'a str,
+    pub sha256: &'a str,
+    /// Digest matched the manifest
+    pub verified: bool,
+    /// The file, or only its first `Renderer::preview_bytes` when it is over the inline limit
+    pub bytes: &'a [u8],
+    /// Size of the whole file in bytes
+    pub size: u64,
+    /// Artifacts an `llm-proof` claim may cite, by id
+    pub evidence: &'a BTreeMap<String, EvidenceLink>,
+}
+
+pub trait Renderer: Sync {
+    /// The manifest `render` value this renderer handles.
+    fn hint(&self) -> &'static str;
+
+    /// Media types an artifact with this hint may declare (from `render_hints::HINTS`).
+    fn media_types(&self) -> &'static [&'static str] {
+        render_hints::lookup(self.hint()).map_or(&[], |h| h.media_types)
+    }
+
+    /// Artifact body as safe HTML.
+    fn render(&self, input: &Input) -> Result<String>;
+
+    /// Whether `render` inlines the file, so oversized artifacts are replaced by a download card.
+    fn inlines_file(&self) -> bool {
+        false
+    }
+
+    /// Leading bytes `render` needs from a file over the inline limit; `None` for the whole file.
+    fn preview_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether Proofdown may show the artifact with `<artifact.viewer kind="{hint}">`.
+    fn viewer(&self) -> bool {
+        false
+    }
+
+    /// Headline numbers for the overview and feed, from the first artifact with this hint.
+    fn kpis(&self, _bytes: &[u8]) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Badge published as `/badge/{name}.svg`, as `(name, badge)`. `bytes` is `None` when the
+    /// manifest has no readable artifact with this hint.
+    fn badge(&self, _bytes: Option<&[u8]>) -> Option<(&'static str, ShieldsBadge)> {
+        None
+    }
+
+    /// Whether `media_type` is one of `media_types`, ignoring parameters and case.
+    fn accepts(&self, media_type: &str) -> bool {
+        render_hints::lookup(self.hint()).is_some_and(|h| h.accepts(media_type))
+    }
+}
+
+/// Every render hint, in the order of the schema's `render` enum.
+pub static REGISTRY: &[&dyn Renderer] = &[
+    &Markdown,
+    &Json,
+    &CoverageTable,
+    &TestSummaryCards,
+    &Image,
+    &RepoFile,
+    &RepoBundle,
+    &RepoSymbols,
+    &ProofdownSource,
+    &ProofdownAst,
+    &LlmProofViewer,
+    &Text,
+    &Raw,
+];
+
+/// The renderer for a manifest `render` value.
+pub fn lookup(hint: &str) -> Option<&'static dyn Renderer> {
+    REGISTRY.iter().copied().find(|r| r.hint() == hint)
+}
+
+/// Checks an artifact's `render` value and media type, and returns its renderer.
+pub fn check_artifact(id: &str, hint: &str, media_type: &str) -> Result<&'static dyn Renderer> {
+    render_hints::check_artifact(id, hint, media_type).map_err(|e| anyhow!(e))?;
+    lookup(hint).ok_or_else(|| anyhow!("no renderer for render: {}", hint))
+}
+
+struct Markdown;
+impl Renderer for Markdown {
+    fn hint(&self) -> &'static str { "markdown" }
+    fn render(&self, input: &Input) -> Result<String> { Ok(crate::render_markdown(&String::from_utf8_lossy(input.bytes))) }
+    fn inlines_file(&self) -> bool { true }
+}
+
+struct Json;
+impl Renderer for Json {
+    fn hint(&self) -> &'static str { "json" }
+    fn render(&self, input: &Input) -> Result<String> { crate::render_json_pretty(input.bytes) }
+    fn inlines_file(&self) -> bool { true }
+}
+
+struct CoverageTable;
+impl Renderer for CoverageTable {
+    fn hint(&self) -> &'static str { "table:coverage" }
+    fn render(&self, input: &Input) -> Result<String> { crate::render_coverage(input.bytes) }
+    fn kpis(&self, bytes: &[u8]) -> Vec<(&'static str, String)> {
+        match serde_json::from_slice::<Coverage>(bytes) {
+            Ok(Coverage { total: Some(t), .. }) => vec![("Coverage", format!("{:.1}%", t.pct))],
+            _ => Vec::new(),
+        }
+    }
+    fn badge(&self, bytes: Option<&[u8]>) -> Option<(&'static str, ShieldsBadge)> {
+        let badge = match bytes.map(serde_json::from_slice::<Coverage>) {
+            Some(Ok(c)) => badges::badge_coverage(&badges::Coverage { total: c.total.map(|t| badges::CoverageTotal { pct: t.pct }) }),
+            _ => badges::badge_error("coverage", "error"),
+        };
+        Some(("coverage", badge))
+    }
+}
+
+struct TestSummaryCards;
+impl Renderer for TestSummaryCards {
+    fn hint(&self) -> &'static str { "summary:test" }
+    fn render(&self, input: &Input) -> Result<String> { crate::render_tests_summary(input.bytes) }
+    fn kpis(&self, bytes: &[u8]) -> Vec<(&'static str, String)> {
+        match serde_json::from_slice::<TestSummary>(bytes) {
+            Ok(s) => vec![
+                ("Tests", format!("{} total, {} passed, {} failed", s.total, s.passed, s.failed)),
+                ("Duration", format!("{:.2}s", s.duration_seconds)),
+            ],
+            Err(_) => Vec::new(),
+        }
+    }
+    fn badge(&self, bytes: Option<&[u8]>) -> Option<(&'static str, ShieldsBadge)> {
+        let badge = match bytes.map(serde_json::from_slice::<TestSummary>) {
+            Some(Ok(s)) => badges::badge_tests(&badges::TestSummary { total: s.total, passed: s.passed, failed: s.failed, duration_seconds: s.duration_seconds }),
+            _ => badges::badge_error("tests", "error"),
+        };
+        Some(("tests", badge))
+    }
+}
+
+struct Image;
+impl Renderer for Image {
+    fn hint(&self) -> &'static str { "image" }
+    fn render(&self, input: &Input) -> Result<String> { Ok(crate::render_image(input.href, input.title)) }    fn preview_bytes(&self) -> Option<usize> { Some(0) }
+}
+
+/// A single source file, shown as numbered text so `repo:` links can target `#Ln`.
+struct RepoFile;
+impl Renderer for RepoFile {
+    fn hint(&self) -> &'static str { "repo:file" }
+    fn render(&self, input: &Input) -> Result<String> {
+        match crate::decode_text(input.bytes) {
+            Some((encoding, text)) => Ok(crate::render_text(encoding, &text)),
+            None => Ok(source(input.bytes)),
+        }
+    }
+    fn inlines_file(&self) -> bool { true }
+}
+
+/// A source archive; its files are reached through `repo:` links, so the page offers the download.
+struct RepoBundle;
+impl Renderer for RepoBundle {
+    fn hint(&self) -> &'static str { "repo:bundle" }
+    fn render(&self, input: &Input) -> Result<String> { Ok(download_card("Source archive", input)) }    fn preview_bytes(&self) -> Option<usize> { Some(0) }
+}
+
+/// Symbol index (ctags/LSIF JSON) used by `sym:` links.
+struct RepoSymbols;
+impl Renderer for RepoSymbols {
+    fn hint(&self) -> &'static str { "repo:symbols" }
+    fn render(&self, input: &Input) -> Result<String> { crate::render_json_pretty(input.bytes) }
+    fn inlines_file(&self) -> bool { true }
+}
+
+/// Source view; the rendered page is /p/{id}/ (builds with the Proofdown parser).
+struct ProofdownSource;
+impl Renderer for ProofdownSource {
+    fn hint(&self) -> &'static str { "proofdown" }
+    fn render(&self, input: &Input) -> Result<String> { Ok(source(input.bytes)) }
+}
+
+struct ProofdownAst;
+impl Renderer for ProofdownAst {
+    fn hint(&self) -> &'static str { "proofdown:ast" }
+    fn render(&self, input: &Input) -> Result<String> { crate::render_json_pretty(input.bytes) }
+    fn inlines_file(&self) -> bool { true }
+}
+
+struct LlmProofViewer;
+impl Renderer for LlmProofViewer {
+    fn hint(&self) -> &'static str { "llm-proof" }
+    fn render(&self, input: &Input) -> Result<String> { crate::render_llm_proof(input.bytes, input.evidence) }
+    fn viewer(&self) -> bool { true }
+}
+
+/// Numbered plain text for text files in any common encoding.
+struct Text;
+impl Renderer for Text {
+    fn hint(&self) -> &'static str { "text" }
+    fn render(&self, input: &Input) -> Result<String> {
+        let (encoding, text) = crate::decode_text(input.bytes).ok_or_else(|| anyhow!("{} is not text in a supported encoding", input.id))?;
+        Ok(crate::render_text(encoding, &text))
+    }
+    fn inlines_file(&self) -> bool { true }
+    fn viewer(&self) -> bool { true }
+}
+
+/// Fallback for any media type: text when the bytes decode as text, a hex dump for `application/*`
+/// data, and a metadata card for everything else (images, audio, video, fonts, ...). Only text is
+/// shown whole; an oversized text file gets a preview of its first lines.
+struct Raw;
+impl Renderer for Raw {
+    fn hint(&self) -> &'static str { "raw" }
+    fn render(&self, input: &Input) -> Result<String> {
+        let essence = render_hints::essence(input.media_type);
+        let ty = essence.split('/').next().unwrap_or_default();
+        let whole = input.bytes.len() as u64 >= input.size;
+        if ty == "text" || ty == "application" {
+            if let Some((encoding, text)) = crate::decode_text(utf8_prefix(input.bytes, whole)).filter(|_| !input.bytes.is_empty()) {
+                if whole {
+                    return Ok(crate::render_text(encoding, &text));
+                }
+                let lines = text.rsplit_once('\n').map_or(text.as_str(), |(head, _)| head);
+                return Ok(format!("<p class=\"muted\">Text preview: first {} of {} bytes</p>{}", input.bytes.len(), input.size, crate::render_text(encoding, lines)));
+            }
+        }
+        if ty == "application" && !input.bytes.is_empty() {
+            return Ok(crate::render_hex_dump(input.bytes, input.size));
+        }
+        Ok(crate::render_metadata_card(input.title, input.media_type, input.size, input.sha256, input.href, input.verified))
+    }
+    fn preview_bytes(&self) -> Option<usize> { Some(crate::HEX_PREVIEW_BYTES) }
+    fn viewer(&self) -> bool { true }
+}
+
+/// `bytes` without a UTF-8 sequence cut off at the end, when they are only the start of the file.
+fn utf8_prefix(bytes: &[u8], whole: bool) -> &[u8] {
+    match std::str::from_utf8(bytes) {
+        Err(e) if !whole && e.error_len().is_none() => &bytes[..e.valid_up_to()],
+        _ => bytes,
+    }
+}
+
+fn source(bytes: &[u8]) -> String {
+    format!("<pre><code>{}</code></pre>", html_escape(&String::from_utf8_lossy(bytes)))
+}
+
+fn download_card(kind: &str, input: &Input) -> String {
+    format!(
+        "<div class=\"card\"><strong>{}</strong>: {} bytes. <a href=\"{}\">Download</a></div>",
+        kind,
+        input.size,
+        html_escape(input.href).replace('"', "&quot;")
+    )
+}
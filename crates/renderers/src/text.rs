@@ -0,0 +1,82 @@
+// Generic safe viewers behind the `text` and `raw` render hints: numbered plain text with the
+// encoding detected from the bytes, a hex dump preview for binaries, and a metadata card for
+// everything else. Nothing from the file is ever interpreted as markup.
+
+use crate::html_escape;
+
+/// Bytes shown in a hex dump preview
+pub const HEX_PREVIEW_BYTES: usize = 512;
+
+/// Decode text: a UTF-8 or UTF-16 byte order mark, else UTF-8, else Latin-1. `None` for data that
+/// looks binary (NUL bytes or control characters outside whitespace).
+pub fn decode_text(bytes: &[u8]) -> Option<(&'static str, String)> {
+    let (encoding, text) = if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        ("UTF-8", String::from_utf8(rest.to_vec()).ok()?)
+    } else if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        ("UTF-16LE", utf16(rest, u16::from_le_bytes)?)
+    } else if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        ("UTF-16BE", utf16(rest, u16::from_be_bytes)?)
+    } else if let Ok(s) = std::str::from_utf8(bytes) {
+        ("UTF-8", s.to_string())
+    } else {
+        ("ISO-8859-1", bytes.iter().map(|&b| b as char).collect())
+    };
+    let binary = text.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t' | '\u{c}'));
+    if binary { None } else { Some((encoding, text)) }
+}
+
+fn utf16(bytes: &[u8], word: fn([u8; 2]) -> u16) -> Option<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    String::from_utf16(&bytes.chunks_exact(2).map(|c| word([c[0], c[1]])).collect::<Vec<_>>()).ok()
+}
+
+/// Plain text with line numbers; each line number is an anchor (`#L12`).
+pub fn render_text(encoding: &str, text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines.len().to_string().len();
+    let mut body = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let n = i + 1;
+        body.push_str(&format!("<span class=\"ln\" id=\"L{}\" aria-hidden=\"true\">{:>w$}</span> {}\n", n, n, html_escape(line), w = width));
+    }
+    format!(
+        "<p class=\"muted\">{} · {} line{}</p><pre class=\"text-lines\"><code>{}</code></pre>",
+        encoding,
+        lines.len(),
+        if lines.len() == 1 { "" } else { "s" },
+        body
+    )
+}
+
+/// Offset, hex and ASCII columns for the first `HEX_PREVIEW_BYTES` bytes of a `size`-byte file.
+pub fn render_hex_dump(bytes: &[u8], size: u64) -> String {
+    let shown = &bytes[..bytes.len().min(HEX_PREVIEW_BYTES)];
+    let mut rows = String::new();
+    for (i, chunk) in shown.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect();
+        rows.push_str(&format!("{:08x}  {:<47}  {}\n", i * 16, hex.join(" "), html_escape(&ascii)));
+    }
+    let note = if size > shown.len() as u64 {
+        format!("Binary data: first {} of {} bytes", shown.len(), size)
+    } else {
+        format!("Binary data: {} bytes", size)
+    };
+    format!("<p class=\"muted\">{}</p><pre class=\"hex-dump\"><code>{}</code></pre>", note, rows)
+}
+
+/// What a reader needs to fetch and check the file when it cannot be shown inline.
+pub fn render_metadata_card(title: &str, media_type: &str, size: u64, sha256: &str, href: &str, verified: bool) -> String {
+    let status = if verified { "<span class=\"badge ok\">verified</span>" } else { "<span class=\"badge err\">digest mismatch</span>" };
+    format!(
+        "<div class=\"card\"><h3>{}</h3><dl class=\"meta\"><dt>Media</dt><dd>{}</dd><dt>Size</dt><dd>{} bytes</dd><dt>SHA-256</dt><dd><code class=\"digest\">{}</code></dd></dl><p><a href=\"{}\">Download</a> {}</p></div>",
+        html_escape(title),
+        html_escape(media_type),
+        size,
+        html_escape(sha256),
+        html_escape(href).replace('"', "&quot;"),
+        status
+    )
+}
@@ -40,3 +40,124 @@ fn tests_summary_cards_have_expected_counts() {
     assert!(html.contains("<strong>9</strong>"));
     assert!(html.contains("<strong>1</strong>"));
 }
+
+#[test]
+fn llm_proof_flags_missing_and_mismatched_evidence() {
+    let proof = serde_json::json!({
+        "title": "Login <hardening>",
+        "claims": [
+            {"id": "lockout", "statement": "Accounts lock after 5 failures", "status": "proven", "evidence": ["tests-summary"], "tests": ["auth::lockout"]},
+            {"id": "audit", "statement": "Lockouts are audited", "status": "partial", "evidence": ["coverage", "audit-log"]},
+            {"id": "rate", "statement": "Rate limited", "status": "proven"}
+        ]
+    });
+    let mut evidence = std::collections::BTreeMap::new();
+    evidence.insert("tests-summary".to_string(), EvidenceLink { title: "Test Summary".into(), href: "/a/tests-summary/".into(), verified: true });
+    evidence.insert("coverage".to_string(), EvidenceLink { title: "Coverage".into(), href: "/a/coverage/".into(), verified: false });
+    let html = render_llm_proof(serde_json::to_string(&proof).unwrap().as_bytes(), &evidence).unwrap();
+    assert!(html.contains("<h3>Login &lt;hardening&gt;</h3>"));
+    assert!(html.contains("<li class=\"claim\" id=\"claim-lockout\">"));
+    assert!(html.contains("<a href=\"/a/tests-summary/\">Test Summary</a> <span class=\"badge ok\">verified</span>"));
+    assert!(html.contains("<li class=\"claim flagged\" id=\"claim-audit\">"));
+    assert!(html.contains("coverage failed digest verification; audit-log is not in the manifest"));
+    assert!(html.contains("marked proven without evidence or tests"));
+    assert!(html.contains("3 claims · 2 proven · 2 flagged"));
+}
+
+#[test]
+fn llm_proof_rejects_unknown_status() {
+    let proof = br#"{"claims":[{"id":"x","statement":"y","status":"probably"}]}"#;
+    assert!(render_llm_proof(proof, &Default::default()).is_err());
+}
+
+#[test]
+fn registry_checks_hints_and_media_types() {
+    assert!(check_artifact("shot", "image", "image/png").is_ok());
+    assert!(check_artifact("notes", "markdown", "text/markdown").is_ok());
+    let err = check_artifact("shot", "image", "application/json").err().unwrap().to_string();
+    assert_eq!(err, "render image does not accept media type application/json for id shot (expected image/*)");
+    assert_eq!(check_artifact("x", "html", "text/html").err().unwrap().to_string(), "unknown render: html for id x");
+    assert!(check_artifact("summary", "json", "application/json; charset=utf-8").is_ok());
+    let hints: Vec<&str> = REGISTRY.iter().map(|r| r.hint()).collect();
+    assert_eq!(hints.len(), hints.iter().collect::<std::collections::BTreeSet<_>>().len(), "hints are unique");
+    let contract: Vec<&str> = render_hints::HINTS.iter().map(|h| h.name).collect();
+    assert_eq!(hints, contract, "every hint in render_hints::HINTS has one renderer");
+}
+
+#[test]
+fn registry_renders_kpis_and_badges() {
+    let summary = br#"{"total": 10, "passed": 9, "failed": 1, "duration_seconds": 1.5}"#;
+    let tests = lookup("summary:test").unwrap();
+    assert_eq!(tests.kpis(summary), vec![("Tests", "10 total, 9 passed, 1 failed".to_string()), ("Duration", "1.50s".to_string())]);
+    let (name, badge) = tests.badge(Some(summary)).unwrap();
+    assert_eq!((name, badge.message.as_str(), badge.color.as_str()), ("tests", "9/10 passed", "orange"));
+    assert_eq!(tests.badge(None).unwrap().1.message, "error");
+    assert!(lookup("markdown").unwrap().badge(None).is_none());
+
+    let evidence = Default::default();
+    let input = input("lib.rs", "text/x-rust", b"fn a() -> Vec<u8> {}", &evidence);
+    assert_eq!(
+        lookup("repo:file").unwrap().render(&input).unwrap(),
+        "<p class=\"muted\">UTF-8 · 1 line</p><pre class=\"text-lines\"><code><span class=\"ln\" id=\"L1\" aria-hidden=\"true\">1</span> fn a() -&gt; Vec&lt;u8&gt; {}\n</code></pre>"
+    );
+    assert!(lookup("llm-proof").unwrap().viewer());
+}
+
+fn input<'a>(title: &'a str, media_type: &'a str, bytes: &'a [u8], evidence: &'a std::collections::BTreeMap<String, EvidenceLink>) -> Input<'a> {
+    Input { id: "x", title, href: "/assets/x/file", media_type, sha256: "ab12", verified: true, bytes, size: bytes.len() as u64, evidence }
+}
+
+#[test]
+fn text_decoding_detects_encodings_and_binaries() {
+    assert_eq!(decode_text(b"plain\n"), Some(("UTF-8", "plain\n".to_string())));
+    assert_eq!(decode_text(b"\xEF\xBB\xBFbom"), Some(("UTF-8", "bom".to_string())));
+    assert_eq!(decode_text(b"\xFF\xFEh\x00i\x00"), Some(("UTF-16LE", "hi".to_string())));
+    assert_eq!(decode_text(b"\xFE\xFF\x00h\x00i"), Some(("UTF-16BE", "hi".to_string())));
+    assert_eq!(decode_text(b"caf\xE9"), Some(("ISO-8859-1", "café".to_string())));
+    assert_eq!(decode_text(b"\x7FELF\x02\x01\x00"), None);
+}
+
+#[test]
+fn raw_viewer_falls_back_by_content() {
+    let evidence = Default::default();
+    let raw = lookup("raw").unwrap();
+    assert!(raw.accepts("video/mp4") && raw.viewer());
+
+    let text = raw.render(&input("log", "text/plain", b"a\n<b>\n", &evidence)).unwrap();
+    assert!(text.contains("UTF-8 · 2 lines"));
+    assert!(text.contains("<span class=\"ln\" id=\"L2\" aria-hidden=\"true\">2</span> &lt;b&gt;"));
+
+    let bin: Vec<u8> = (0u8..=255).cycle().take(600).collect();
+    let hex = raw.render(&input("core", "application/octet-stream", &bin, &evidence)).unwrap();
+    assert!(hex.contains("Binary data: first 512 of 600 bytes"));
+    assert!(hex.contains("00000000  00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f  ................\n"));
+    assert!(hex.contains("00000040  40 41 42 43 44 45 46 47 48 49 4a 4b 4c 4d 4e 4f  @ABCDEFGHIJKLMNO\n"));
+    assert!(!hex.contains("00000200"));
+
+    let card = raw.render(&input("Demo <video>", "video/mp4", b"\x00\x00\x00\x18ftyp", &evidence)).unwrap();
+    assert!(card.contains("<h3>Demo &lt;video&gt;</h3>"));
+    assert!(card.contains("<dd>8 bytes</dd>"));
+    assert!(card.contains("<a href=\"/assets/x/file\">Download</a> <span class=\"badge ok\">verified</span>"));
+
+    assert!(lookup("text").unwrap().render(&input("core", "text/plain", &bin, &evidence)).is_err());
+}
+
+#[test]
+fn raw_viewer_previews_the_start_of_large_files() {
+    let evidence = Default::default();
+    let raw = lookup("raw").unwrap();
+    assert!(!raw.inlines_file());
+    assert_eq!(raw.preview_bytes(), Some(512));
+
+    // Only the first bytes of a 4 KiB file were read
+    let head = |media_type, bytes| Input { size: 4096, ..input("big", media_type, bytes, &evidence) };
+    let text = raw.render(&head("text/plain", &"one\ntwo\nthr\u{e9}".as_bytes()[..12])).unwrap();
+    assert!(text.contains("Text preview: first 12 of 4096 bytes"), "{}", text);
+    assert!(text.contains("UTF-8 · 2 lines"), "a cut line or character is dropped: {}", text);
+
+    let hex = raw.render(&head("application/octet-stream", &[0, 1, 2, 3])).unwrap();
+    assert!(hex.contains("Binary data: first 4 of 4096 bytes"), "{}", hex);
+
+    let card = raw.render(&head("video/mp4", b"")).unwrap();
+    assert!(card.contains("<dd>4096 bytes</dd>"), "{}", card);
+}
@@ -4,6 +4,7 @@ use ed25519_dalek::{Signer, SigningKey};
 use base64::Engine as _;
 use hex::ToHex;
 use manifest_contract as mc;
+use provenance_ssg::lint;
 use rand::rngs::OsRng;
 use serde_json::Value;
 use sha2::{Digest, Sha256};
@@ -50,10 +51,25 @@ enum Cmd {
         /// Project root directory (where artifact paths are relative)
         #[arg(long, default_value = ".")]
         root: PathBuf,
-        /// Path to manifest.json
+        /// Path to the manifest JSON relative to --root
         #[arg(long, default_value = ".provenance/manifest.json")]
         manifest: PathBuf,
     },
+    /// Verify artifact SHA-256 digests against the manifest, and its signature when --pubkey is given
+    Verify {
+        /// Project root directory (where artifact paths are relative)
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Path to the manifest JSON relative to --root
+        #[arg(long, default_value = ".provenance/manifest.json")]
+        manifest: PathBuf,
+        /// Public key (Base64 or hex) to check manifest.json.sig against
+        #[arg(long)]
+        pubkey: Option<String>,
+        /// Only verify the artifact with this id
+        #[arg(long)]
+        artifact: Option<String>,
+    },
     /// Canonicalize and sign manifest (Ed25519, Base64 signature file)
     Sign {
         /// Path to manifest.json
@@ -78,6 +94,24 @@ enum Cmd {
         #[arg(long)]
         pubkey_out: PathBuf,
     },
+    /// Check a Proofdown file against the component whitelist and the manifest, without building
+    LintPml {
+        /// Project root directory (where artifact paths are relative)
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Path to the manifest JSON relative to --root
+        #[arg(long, default_value = ".provenance/manifest.json")]
+        manifest: PathBuf,
+        /// Proofdown file relative to --root (defaults to front_page.markup)
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Path to JSON Schema (default schemas/manifest.schema.json)
+        #[arg(long)]
+        schema_path: Option<PathBuf>,
+        /// Output format: `text` (file:line:col lines) or `json`
+        #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+    },
     /// Generate a large JSON file for truncation tests
     GenLargeJson {
         /// Output path for JSON file
@@ -92,9 +126,12 @@ enum Cmd {
 fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli.cmd {
-        Cmd::UpdateSha { root, manifest } => update_sha(&root, &manifest),
+        // Like the site generator and lint-pml, --manifest is relative to --root
+        Cmd::UpdateSha { root, manifest } => update_sha(&root, &root.join(manifest)),
+        Cmd::Verify { root, manifest, pubkey, artifact } => verify(&root, &root.join(manifest), pubkey.as_deref(), artifact.as_deref()),
         Cmd::Sign { manifest, privkey, sig_out, pubkey_out } => sign_manifest(&manifest, &privkey, sig_out.as_ref(), pubkey_out.as_ref()),
         Cmd::GenTestKey { privkey_out, pubkey_out } => gen_test_key(&privkey_out, &pubkey_out),
+        Cmd::LintPml { root, manifest, file, schema_path, format } => lint_pml(&root, &manifest, file.as_deref(), schema_path, &format),
         Cmd::GenLargeJson { out, size_mb } => gen_large_json(&out, size_mb),
     }
 }
@@ -116,6 +153,62 @@ fn update_sha(root: &Path, manifest_path: &Path) -> Result<()> {
     Ok(())
 }
 
+fn verify(root: &Path, manifest_path: &Path, pubkey: Option<&str>, only: Option<&str>) -> Result<()> {
+    let (m, val) = mc::load_manifest(manifest_path)?;
+    if let Some(pk) = pubkey {
+        let sig_path = manifest_path.with_extension("json.sig");
+        let sig = fs::read_to_string(&sig_path).with_context(|| format!("read {}", sig_path.display()))?;
+        if !mc::ed25519_verify(&mc::canonicalize(&val), sig.trim(), pk)? {
+            return Err(anyhow!("signature {} does not match the manifest", sig_path.display()));
+        }
+        println!("signature OK ({})", sig_path.display());
+    }
+    let mut checked = 0;
+    let mut failed = 0;
+    for art in m.artifacts.iter().filter(|a| only.is_none_or(|id| a.id == id)) {
+        checked += 1;
+        let src = root.join(&art.path);
+        match sha256_file(&src) {
+            Ok(digest) if digest == art.sha256 => println!("{} OK {}", art.id, digest),
+            Ok(digest) => {
+                failed += 1;
+                println!("{} MISMATCH expected {} computed {}", art.id, art.sha256, digest);
+            }
+            Err(e) => {
+                failed += 1;
+                println!("{} MISSING {}: {}", art.id, src.display(), e);
+            }
+        }
+    }
+    if let (Some(id), 0) = (only, checked) {
+        return Err(anyhow!("artifact {} is not in the manifest", id));
+    }
+    if failed > 0 {
+        return Err(anyhow!("{} of {} artifacts failed verification", failed, checked));
+    }
+    Ok(())
+}
+
+fn lint_pml(root: &Path, manifest: &Path, file: Option<&Path>, schema_path: Option<PathBuf>, format: &str) -> Result<()> {
+    let report = lint::lint_pml(root, manifest, schema_path, file)?;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for d in &report.diagnostics {
+            let severity = match d.severity {
+                lint::Severity::Error => "error",
+                lint::Severity::Warning => "warning",
+            };
+            println!("{}:{}:{}: {}[{}]: {}", report.file, d.line, d.column, severity, d.code, d.message);
+        }
+        println!("{}: {} errors, {} warnings", report.file, report.errors, report.warnings);
+    }
+    if report.errors > 0 {
+        return Err(anyhow!("{} has {} lint errors", report.file, report.errors));
+    }
+    Ok(())
+}
+
 fn sign_manifest(manifest_path: &Path, privkey_path: &Path, sig_out: Option<&PathBuf>, pubkey_out: Option<&PathBuf>) -> Result<()> {
     let (_m, val) = mc::load_manifest(manifest_path)?;
     let canonical = mc::canonicalize(&val);
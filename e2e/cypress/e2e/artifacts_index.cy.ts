@@ -27,4 +27,16 @@ describe('Artifacts index', () => {
       });
     });
   });
+
+  it('filters rows client-side from search_index.json', () => {
+    cy.visit('/artifacts/');
+    cy.get('#artifact-count').should('contain.text', 'of');
+    cy.get('#q').clear().type('coverage');
+    cy.get('table tbody tr:visible').should('have.length', 1).first().should('have.attr', 'data-id', 'coverage');
+    cy.get('#q').clear();
+    cy.get('#kind').select('markdown');
+    cy.get('table tbody tr:visible').each(($tr) => {
+      cy.wrap($tr).find('td').eq(1).should('have.text', 'markdown');
+    });
+  });
 });